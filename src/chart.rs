@@ -0,0 +1,220 @@
+//! Terminal charts: sparklines, braille line charts and candlestick charts.
+
+/// Width used when the terminal size can't be read (e.g. output is piped).
+const DEFAULT_WIDTH: usize = 80;
+const SPARK_CHARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+/// Braille dot bits by `[row][column]` within a 2x4 cell.
+const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+const BRAILLE_BLANK: u32 = 0x2800;
+
+/// Columns of the terminal on stdout, else `$COLUMNS`, else 80.
+pub fn terminal_width() -> usize {
+    #[cfg(unix)]
+    {
+        // SAFETY: TIOCGWINSZ only writes a `winsize` into the struct we pass.
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0 && size.ws_col > 0 {
+            return size.ws_col as usize;
+        }
+    }
+    std::env::var("COLUMNS").ok().and_then(|c| c.parse().ok()).filter(|c| *c > 0).unwrap_or(DEFAULT_WIDTH)
+}
+
+/// One block character per value, scaled between the series' low and high. A flat series
+/// is drawn at mid height.
+pub fn sparkline(values: &[f64]) -> String {
+    let (low, high) = bounds(values);
+    values
+        .iter()
+        .map(|v| {
+            let level = if high > low { (v - low) / (high - low) * (SPARK_CHARS.len() - 1) as f64 } else { 3.0 };
+            SPARK_CHARS[level.round() as usize]
+        })
+        .collect()
+}
+
+/// Shrink a series to at most `width` points, keeping the last value of each bucket.
+pub fn resample(values: &[f64], width: usize) -> Vec<f64> {
+    if values.len() <= width || width == 0 {
+        return values.to_vec();
+    }
+    (1..=width).map(|i| values[i * values.len() / width - 1]).collect()
+}
+
+/// A braille line chart of `height` rows, with the high and low labelled on a y axis.
+/// The plot uses whatever of `width` columns the labels leave.
+pub fn line_chart(values: &[f64], width: usize, height: usize) -> Vec<String> {
+    if values.is_empty() || height == 0 {
+        return Vec::new();
+    }
+    let (low, high) = bounds(values);
+    let (labels, label_width) = axis_labels(low, high, height);
+    let cols = width.saturating_sub(label_width + 2).max(1);
+    let (px_w, px_h) = (cols * 2, height * 4);
+    let points = resample(values, px_w);
+
+    let y_of = |v: f64| {
+        if high > low { ((v - low) / (high - low) * (px_h - 1) as f64).round() as usize } else { px_h / 2 }
+    };
+    let x_of = |i: usize| if points.len() > 1 { i * (px_w - 1) / (points.len() - 1) } else { 0 };
+    let mut cells = vec![vec![0u32; cols]; height];
+    let mut plot = |x: usize, y: usize| {
+        let row = px_h - 1 - y;
+        cells[row / 4][x / 2] |= BRAILLE_DOTS[row % 4][x % 2];
+    };
+
+    let mut prev: Option<(usize, usize)> = None;
+    for (i, v) in points.iter().enumerate() {
+        let (x, y) = (x_of(i), y_of(*v));
+        match prev {
+            Some((x0, y0)) => {
+                // Fill every column up to this point, joining each to the one before vertically
+                let slope = (y as f64 - y0 as f64) / (x - x0) as f64;
+                for xi in x0 + 1..=x {
+                    let yi = (y0 as f64 + slope * (xi - x0) as f64).round() as usize;
+                    let from = (y0 as f64 + slope * (xi - x0 - 1) as f64).round() as usize;
+                    for yy in from.min(yi)..=from.max(yi) {
+                        plot(xi, yy);
+                    }
+                }
+            }
+            None => plot(x, y),
+        }
+        prev = Some((x, y));
+    }
+
+    cells
+        .iter()
+        .zip(labels)
+        .map(|(row, label)| {
+            let line: String = row.iter().map(|bits| char::from_u32(BRAILLE_BLANK | bits).unwrap_or(' ')).collect();
+            format!("{:>label_width$} ┤{}", label, line, label_width = label_width)
+        })
+        .collect()
+}
+
+/// One candle: open, high, low, close.
+pub type Ohlc = (f64, f64, f64, f64);
+
+/// A candlestick chart of `height` rows, one column per candle. Rising candles have solid
+/// bodies (`█`), falling ones shaded bodies (`░`), wicks are `│`. With more candles than
+/// columns, neighbouring candles are merged.
+pub fn candle_chart(candles: &[Ohlc], width: usize, height: usize) -> Vec<String> {
+    if candles.is_empty() || height == 0 {
+        return Vec::new();
+    }
+    let low = candles.iter().map(|c| c.2).fold(f64::INFINITY, f64::min);
+    let high = candles.iter().map(|c| c.1).fold(f64::NEG_INFINITY, f64::max);
+    let (labels, label_width) = axis_labels(low, high, height);
+    let cols = width.saturating_sub(label_width + 2).max(1);
+    let candles = merge_candles(candles, cols);
+
+    let row_of = |v: f64| {
+        if high > low {
+            height - 1 - ((v - low) / (high - low) * (height - 1) as f64).round() as usize
+        } else {
+            height / 2
+        }
+    };
+    let mut grid = vec![vec![' '; candles.len()]; height];
+    for (col, (open, high, low, close)) in candles.iter().enumerate() {
+        for row in grid.iter_mut().take(row_of(*low) + 1).skip(row_of(*high)) {
+            row[col] = '│';
+        }
+        let body = if close >= open { '█' } else { '░' };
+        for row in grid.iter_mut().take(row_of(open.min(*close)) + 1).skip(row_of(open.max(*close))) {
+            row[col] = body;
+        }
+    }
+
+    grid.iter()
+        .zip(labels)
+        .map(|(row, label)| {
+            let line: String = row.iter().collect();
+            format!("{:>label_width$} ┤{}", label, line, label_width = label_width)
+        })
+        .collect()
+}
+
+/// Merge consecutive candles so at most `width` remain.
+fn merge_candles(candles: &[Ohlc], width: usize) -> Vec<Ohlc> {
+    if candles.len() <= width {
+        return candles.to_vec();
+    }
+    (0..width)
+        .map(|i| {
+            let group = &candles[i * candles.len() / width..(i + 1) * candles.len() / width];
+            let high = group.iter().map(|c| c.1).fold(f64::NEG_INFINITY, f64::max);
+            let low = group.iter().map(|c| c.2).fold(f64::INFINITY, f64::min);
+            (group[0].0, high, low, group[group.len() - 1].3)
+        })
+        .collect()
+}
+
+fn bounds(values: &[f64]) -> (f64, f64) {
+    let low = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let high = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    (low, high)
+}
+
+/// High on the top row, low on the bottom row, blanks in between.
+fn axis_labels(low: f64, high: f64, height: usize) -> (Vec<String>, usize) {
+    let mut labels = vec![String::new(); height];
+    labels[0] = format!("{:.3}", high);
+    if height > 1 {
+        labels[height - 1] = format!("{:.3}", low);
+    }
+    let width = labels.iter().map(|l| l.len()).max().unwrap_or(0);
+    (labels, width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[0.0, 0.5, 1.0]), "▁▅█");
+        assert_eq!(sparkline(&[0.3, 0.3]), "▄▄");
+        assert_eq!(sparkline(&[]), "");
+    }
+
+    #[test]
+    fn test_resample_keeps_bucket_ends() {
+        let values: Vec<f64> = (1..=10).map(f64::from).collect();
+        assert_eq!(resample(&values, 5), vec![2.0, 4.0, 6.0, 8.0, 10.0]);
+        assert_eq!(resample(&values, 20), values);
+    }
+
+    #[test]
+    fn test_line_chart_dimensions_and_extremes() {
+        let values = [0.2, 0.4, 0.6, 0.8];
+        let rows = line_chart(&values, 20, 3);
+        assert_eq!(rows.len(), 3);
+        assert!(rows.iter().all(|r| r.chars().count() == 20));
+        assert!(rows[0].starts_with("0.800 ┤"));
+        assert!(rows[2].starts_with("0.200 ┤"));
+        // The first point is bottom left, the last top right
+        let cell = |row: &str, col: usize| row.chars().nth(7 + col).unwrap() as u32 - BRAILLE_BLANK;
+        assert_ne!(cell(&rows[2], 0) & 0x40, 0);
+        assert_ne!(cell(&rows[0], 12) & 0x08, 0);
+    }
+
+    #[test]
+    fn test_candle_chart() {
+        let candles = [(0.4, 0.6, 0.3, 0.5), (0.5, 0.5, 0.2, 0.3)];
+        let rows = candle_chart(&candles, 10, 5);
+        assert_eq!(rows.len(), 5);
+        let columns: Vec<String> =
+            (0..2).map(|c| rows.iter().map(|r| r.chars().nth(7 + c).unwrap()).collect()).collect();
+        // Rows span 0.6 at the top to 0.2 at the bottom, 0.1 apart
+        assert_eq!(columns[0], "│██│ ");
+        assert_eq!(columns[1], " ░░░│");
+    }
+
+    #[test]
+    fn test_merge_candles() {
+        let candles = [(1.0, 2.0, 0.5, 1.5), (1.5, 3.0, 1.0, 2.5), (2.5, 2.6, 2.0, 2.1)];
+        assert_eq!(merge_candles(&candles, 1), vec![(1.0, 3.0, 0.5, 2.1)]);
+    }
+}
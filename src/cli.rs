@@ -1,4 +1,5 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+use rust_decimal::Decimal;
 
 #[derive(Parser)]
 #[command(name = "polymarket-cli")]
@@ -7,6 +8,14 @@ use clap::{Parser, Subcommand};
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+
+    /// Polygon RPC URL to use instead of the defaults. Repeat to give several endpoints for failover.
+    #[arg(long, global = true)]
+    pub rpc_url: Vec<String>,
+
+    /// Log progress, such as the RPC endpoint serving each call. `RUST_LOG` overrides this.
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
 }
 
 #[derive(Subcommand)]
@@ -15,6 +24,46 @@ pub enum Commands {
     Search {
         /// Keywords to search for
         query: String,
+        /// Only markets with this status: active, closed, archived or all
+        #[arg(long)]
+        status: Option<String>,
+        /// Only events with this tag or category (slug or label)
+        #[arg(long)]
+        tag: Option<String>,
+        /// Only markets with at least this much liquidity (USDC); applied to the fetched page only
+        #[arg(long)]
+        min_liquidity: Option<Decimal>,
+        /// Only markets with at least this much total volume (USDC); applied to the fetched page only
+        #[arg(long)]
+        min_volume: Option<Decimal>,
+        /// Only markets ending before this date (YYYY-MM-DD, RFC 3339, or a duration from now like 7d); applied to the fetched page only
+        #[arg(long)]
+        ends_before: Option<String>,
+        /// Only markets ending after this date (YYYY-MM-DD, RFC 3339, or a duration from now like 7d); applied to the fetched page only
+        #[arg(long)]
+        ends_after: Option<String>,
+        /// Sort by volume, liquidity or end-date (events across pages, markets within the page)
+        #[arg(long)]
+        sort: Option<String>,
+        /// Results per page
+        #[arg(long)]
+        limit: Option<i32>,
+        /// Page of results to fetch, starting at 1
+        #[arg(long, default_value_t = 1)]
+        page: i32,
+        /// One line per market
+        #[arg(long, default_value_t = false)]
+        compact: bool,
+    },
+    /// Show full details of a market
+    Market {
+        /// Market ID, slug, condition ID or outcome token ID
+        market: String,
+    },
+    /// Show every market of an event with implied probabilities and the overround
+    Event {
+        /// Event ID or slug
+        event: String,
     },
     /// See open positions
     Positions {
@@ -24,27 +73,92 @@ pub enum Commands {
     },
     /// See order book for a market
     OrderBook {
-        /// Token ID to fetch order book for
-        #[arg(short, long)]
-        token_id: String,
+        #[command(flatten)]
+        token: TokenArgs,
+
+        /// Stream the book over the WebSocket market channel and redraw it on every change
+        #[arg(long, default_value_t = false)]
+        watch: bool,
+
+        /// Levels to list per side (default: all, or 10 with --watch)
+        #[arg(long)]
+        depth: Option<usize>,
+
+        /// Order size to price a sweep of the book for: shares like 100, or USDC like $100. Repeatable
+        #[arg(long = "size", default_values = ["100", "$100"], conflicts_with = "watch")]
+        sizes: Vec<String>,
     },
     /// See recent trades for a market (Trade history)
     Trade {
-        /// Market ID (or Token ID) to fetch trades for
-        #[arg(short, long)]
-        token_id: String,
+        #[command(flatten)]
+        token: TokenArgs,
+
+        /// Stream trades live until interrupted. With --market and no outcome, follows every outcome
+        #[arg(long, default_value_t = false)]
+        follow: bool,
+
+        /// Only show trades of at least this many shares (with --follow)
+        #[arg(long, requires = "follow")]
+        min_size: Option<Decimal>,
+
+        /// Only trades of this address, as maker or taker
+        #[arg(short, long, conflicts_with = "follow")]
+        user: Option<String>,
+
+        /// Only trades on this side: "buy" or "sell"
+        #[arg(long, conflicts_with = "follow")]
+        side: Option<String>,
+
+        /// Start: YYYY-MM-DD, RFC 3339, or a duration ago like 1d (default: no limit)
+        #[arg(long, conflicts_with = "follow")]
+        from: Option<String>,
+
+        /// End: YYYY-MM-DD, RFC 3339, or a duration ago like 1h (default: now)
+        #[arg(long, conflicts_with = "follow")]
+        to: Option<String>,
+
+        /// Stop after this many trades, newest first (default: all)
+        #[arg(long, conflicts_with = "follow")]
+        limit: Option<usize>,
     },
     /// Get the Midpoint Price for a market
     Midpoint {
-        /// Token ID to fetch midpoint for
-        #[arg(short, long)]
-        token_id: String,
+        #[command(flatten)]
+        token: TokenArgs,
+    },
+    /// Price history of an outcome token as a table, CSV or JSON, with summary stats
+    History {
+        #[command(flatten)]
+        token: TokenArgs,
+
+        /// Spacing between points, e.g. 1m, 1h or 1d
+        #[arg(long, default_value = "1h")]
+        interval: String,
+
+        /// Start: YYYY-MM-DD, RFC 3339, or a duration ago like 7d (default: 7 days before --to)
+        #[arg(long)]
+        from: Option<String>,
+
+        /// End: YYYY-MM-DD, RFC 3339, or a duration ago like 1d (default: now)
+        #[arg(long)]
+        to: Option<String>,
+
+        /// Build OHLC candles with volume from data API trades instead of the CLOB price series
+        #[arg(long, default_value_t = false)]
+        ohlc: bool,
+
+        /// Output format: table, csv or json
+        #[arg(long, default_value = "table")]
+        format: String,
+
+        /// Plot the series in the terminal instead of listing it (a candlestick chart with --ohlc)
+        #[arg(long, default_value_t = false)]
+        chart: bool,
     },
     /// Place an order
     Order {
-        /// Token ID of the outcome
-        #[arg(short, long)]
-        token_id: String,
+        #[command(flatten)]
+        token: TokenArgs,
 
         /// Side to trade: "buy" or "sell"
         #[arg(short, long)]
@@ -58,14 +172,268 @@ pub enum Commands {
         #[arg(short, long)]
         price: Option<String>,
     },
+    /// List our fills from the data API, or from CTF Exchange `OrderFilled` logs with --onchain
+    Fills {
+        /// Maker/taker address to look for. Repeat for several. Defaults to the EOA, proxy and Safe wallets.
+        #[arg(short, long)]
+        user: Vec<String>,
+
+        /// Scan exchange logs on-chain instead of asking the data API
+        #[arg(long, default_value_t = false)]
+        onchain: bool,
+
+        /// First block to scan (defaults to about a day before --to-block)
+        #[arg(long, requires = "onchain")]
+        from_block: Option<u64>,
+
+        /// Last block to scan (defaults to the latest block)
+        #[arg(long, requires = "onchain")]
+        to_block: Option<u64>,
+
+        /// Blocks per log query; halved automatically when the RPC rejects a query
+        #[arg(long, default_value_t = 2_000)]
+        chunk_size: u64,
+    },
     /// Approve tokens for trading
     Approve {
         /// Dry run mode (don't execute transactions)
         #[arg(long, default_value_t = false)]
         dry_run: bool,
     },
+    /// Inspect token approvals granted by our wallets
+    Approvals {
+        #[command(subcommand)]
+        command: ApprovalsCommand,
+    },
     /// Check current status (available cash)
     Status,
+    /// Check ERC-20 token balances, formatted with on-chain decimals
+    Balance {
+        /// Token contract address. Repeat to check several tokens.
+        #[arg(long, required = true)]
+        token: Vec<String>,
+
+        /// Optional user address. If not provided, tries to derive from private key.
+        #[arg(short, long)]
+        user: Option<String>,
+    },
+    /// Redeem winning positions in resolved markets for USDC
+    Redeem {
+        /// Only redeem this market (condition ID)
+        #[arg(short, long)]
+        market: Option<String>,
+
+        /// Dry run mode (show what would be redeemed without sending transactions)
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// Split USDC into a complete set of Yes and No outcome tokens
+    Split {
+        /// Market condition ID
+        #[arg(short, long)]
+        market: String,
+
+        /// Amount of USDC to split
+        #[arg(short, long)]
+        amount: String,
+
+        /// Dry run mode (check balances and approvals without sending transactions)
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// Merge matching Yes and No outcome tokens back into USDC
+    Merge {
+        /// Market condition ID
+        #[arg(short, long)]
+        market: String,
+
+        /// Number of complete sets to merge
+        #[arg(short, long, required_unless_present = "max", conflicts_with = "max")]
+        amount: Option<String>,
+
+        /// Merge every complete set held
+        #[arg(long, default_value_t = false)]
+        max: bool,
+
+        /// Dry run mode (check balances and approvals without sending transactions)
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// Convert No positions in a neg-risk event into Yes positions of the other outcomes plus USDC
+    Convert {
+        /// Condition ID of a market whose No position to convert. Repeat for several markets of the same event.
+        #[arg(short, long, required = true)]
+        market: Vec<String>,
+
+        /// Number of No tokens to convert in each market
+        #[arg(short, long, required_unless_present = "max", conflicts_with = "max")]
+        amount: Option<String>,
+
+        /// Convert as many No tokens as every selected market holds
+        #[arg(long, default_value_t = false)]
+        max: bool,
+
+        /// Dry run mode (preview the resulting holdings without sending transactions)
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// Compare on-chain outcome token balances with the positions reported by the data API
+    Reconcile {
+        /// Optional holder address. If not provided, derives the funding wallet from the private key.
+        #[arg(short, long)]
+        user: Option<String>,
+    },
+    /// Transfer USDC (or another ERC-20) or outcome tokens to another wallet
+    Transfer {
+        /// Recipient address
+        #[arg(long)]
+        to: String,
+
+        /// Amount to send, in token units (e.g. 12.5)
+        #[arg(short, long)]
+        amount: String,
+
+        /// ERC-20 token address to send. Defaults to USDC.e.
+        #[arg(long, conflicts_with = "token_id")]
+        token: Option<String>,
+
+        /// Outcome token ID to send instead of an ERC-20 token
+        #[arg(short, long)]
+        token_id: Option<String>,
+
+        /// Wallet to send from: "eoa", "proxy" or "safe". Defaults to POLYMARKET_SIGNATURE_TYPE.
+        #[arg(long)]
+        from: Option<String>,
+
+        /// Skip the confirmation prompt
+        #[arg(short, long, default_value_t = false)]
+        yes: bool,
+    },
+    /// Rank active markets by spread, midpoint, volume, liquidity and time to close
+    Screen {
+        /// Filter expressions such as `spread<0.02 volume24h>10000 ends<7d`
+        filters: Vec<String>,
+
+        /// Field to sort by, optionally with `:desc`, e.g. `volume24h:desc`
+        #[arg(long)]
+        sort: Option<String>,
+
+        /// Number of active markets to fetch from Gamma, by 24h volume
+        #[arg(long, default_value_t = 1_000)]
+        max_markets: usize,
+
+        /// Number of matches to print
+        #[arg(long, default_value_t = 50)]
+        limit: usize,
+    },
+    /// Scan markets for trading opportunities
+    Scan {
+        #[command(subcommand)]
+        command: ScanCommand,
+    },
+    /// Conditional Tokens utilities
+    Ctf {
+        #[command(subcommand)]
+        command: CtfCommand,
+    },
     /// Upgrade the CLI to the latest version
     Upgrade,
 }
+
+/// An outcome token, given by ID or by market and outcome name.
+#[derive(Args, Clone, Debug)]
+pub struct TokenArgs {
+    /// Token ID of the outcome
+    #[arg(short, long, required_unless_present = "market", conflicts_with = "market")]
+    pub token_id: Option<String>,
+
+    /// Market ID, slug or condition ID, optionally with the outcome appended as `slug:Yes`
+    #[arg(long)]
+    pub market: Option<String>,
+
+    /// Outcome name, e.g. "Yes" or "No" (a unique prefix is enough, except for order)
+    #[arg(long, requires = "market")]
+    pub outcome: Option<String>,
+}
+
+#[derive(Subcommand)]
+pub enum ApprovalsCommand {
+    /// Find every ERC-20 allowance and ERC-1155 operator approval ever granted, and which are still live
+    Audit {
+        /// Address to audit instead of the EOA, proxy and Safe wallets of the private key
+        #[arg(short, long)]
+        user: Option<String>,
+
+        /// First block to scan (defaults to genesis)
+        #[arg(long)]
+        from_block: Option<u64>,
+
+        /// Last block to scan (defaults to the latest block)
+        #[arg(long)]
+        to_block: Option<u64>,
+
+        /// Blocks per log query; halved automatically when the RPC rejects a query
+        #[arg(long, default_value_t = 10_000)]
+        chunk_size: u64,
+
+        /// Revoke live approvals to unknown spenders (or to --spender)
+        #[arg(long, default_value_t = false)]
+        revoke: bool,
+
+        /// Only revoke approvals to this spender, known or not
+        #[arg(long, requires = "revoke")]
+        spender: Option<String>,
+
+        /// Skip the confirmation prompt
+        #[arg(short, long, default_value_t = false)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ScanCommand {
+    /// Find neg-risk events where buying every YES or every NO costs less than the guaranteed payout
+    Arb {
+        /// Event ID or slug to scan. Repeat for several. Defaults to the busiest active neg-risk events.
+        #[arg(short, long)]
+        event: Vec<String>,
+
+        /// Number of active neg-risk events to scan, by 24h volume
+        #[arg(long, default_value_t = 200)]
+        max_events: usize,
+
+        /// Only report opportunities making at least this much USDC
+        #[arg(long, default_value_t = Decimal::ZERO)]
+        min_profit: Decimal,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum CtfCommand {
+    /// Compute condition, collection and position IDs, cross-checked against Gamma when a market is given
+    Ids {
+        /// Gamma market ID, slug, condition ID or token ID to look up the inputs from and check the results against
+        #[arg(short, long)]
+        market: Option<String>,
+
+        /// Oracle address that prepared the condition (defaults to the market's resolver)
+        #[arg(long)]
+        oracle: Option<String>,
+
+        /// Question ID (bytes32)
+        #[arg(long)]
+        question_id: Option<String>,
+
+        /// Number of outcome slots
+        #[arg(long, default_value_t = 2)]
+        outcomes: u32,
+
+        /// Collateral token address (defaults to USDC.e, or wrapped collateral for neg-risk markets)
+        #[arg(long)]
+        collateral: Option<String>,
+
+        /// Treat the market as neg-risk: the Neg Risk Adapter is the oracle and collateral is wrapped
+        #[arg(long, default_value_t = false)]
+        neg_risk: bool,
+    },
+}
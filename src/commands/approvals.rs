@@ -0,0 +1,361 @@
+use anyhow::{Context, Result};
+use alloy::primitives::{B256, Bytes, U256};
+use alloy::providers::{Provider, ProviderBuilder};
+use alloy::rpc::types::{Filter, Log};
+use alloy::sol_types::{SolCall, SolEvent};
+use polymarket_client_sdk::{
+    POLYGON, PRIVATE_KEY_VAR,
+    auth::{LocalSigner, Signer},
+    clob::types::SignatureType,
+    contract_config,
+    types::Address,
+};
+use std::env;
+use std::str::FromStr;
+
+use crate::cli::ApprovalsCommand;
+use crate::commands::approve::{build_approval_targets, format_units, is_unlimited_allowance};
+use crate::commands::transfer::confirm;
+use crate::constants::{USDC_E_ADDRESS, USDC_NATIVE_ADDRESS};
+use crate::contracts::{IERC20, IERC1155, call3, aggregate3, decode_result};
+use crate::rpc;
+use crate::wallet::{funder_address, send_call};
+
+pub async fn execute(command: ApprovalsCommand, rpc_urls: &[String]) -> Result<()> {
+    match command {
+        ApprovalsCommand::Audit { user, from_block, to_block, chunk_size, revoke, spender, yes } => {
+            audit(user, from_block, to_block, chunk_size, revoke, spender, yes, rpc_urls).await
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn audit(
+    user: Option<String>,
+    from_block: Option<u64>,
+    to_block: Option<u64>,
+    chunk_size: u64,
+    revoke: bool,
+    spender: Option<String>,
+    yes: bool,
+    rpc_urls: &[String],
+) -> Result<()> {
+    let signer = match env::var(PRIVATE_KEY_VAR) {
+        Ok(key) => Some(LocalSigner::from_str(&key).context("Invalid private key")?.with_chain_id(Some(POLYGON))),
+        Err(_) => None,
+    };
+    let wallets = match (&user, &signer) {
+        (Some(user), _) => vec![("User", Address::from_str(user).context("Invalid address format")?, None)],
+        (None, Some(signer)) => own_wallets(signer.address())?,
+        (None, None) => anyhow::bail!("PRIVATE_KEY env var not set; pass --user to audit another address"),
+    };
+    let revoke_spender = spender
+        .map(|s| Address::from_str(&s).context("Invalid spender address"))
+        .transpose()?;
+
+    let provider = ProviderBuilder::new().connect_client(rpc::connect(rpc_urls).await?);
+    let latest = provider.get_block_number().await.context("Failed to get latest block")?;
+    let to_block = to_block.unwrap_or(latest).min(latest);
+    // Approvals can be granted on any token, some of which predate every Polymarket contract,
+    // so only genesis is a safe default.
+    let from_block = from_block.unwrap_or(0);
+    anyhow::ensure!(from_block <= to_block, "--from-block is after --to-block");
+    println!("blocks = {}..={}, chunk_size = {}", from_block, to_block, chunk_size);
+    if from_block > 0 {
+        println!("WARNING: incomplete audit, approvals granted before block {} are not listed", from_block);
+    }
+
+    let owners: Vec<Address> = wallets.iter().map(|(_, address, _)| *address).collect();
+    let grants = scan_grants(&provider, &owners, from_block, to_block, chunk_size).await?;
+    let states = read_grant_states(&provider, &grants).await?;
+    let known = known_spenders()?;
+
+    let mut to_revoke = Vec::new();
+    for (label, wallet, signature_type) in &wallets {
+        println!("wallet = {} ({})", wallet, label);
+        let mut revoked = 0;
+        for (grant, state) in grants.iter().zip(&states).filter(|(g, _)| g.owner == *wallet) {
+            let live = match state {
+                Ok(live) => live,
+                Err(e) => {
+                    println!("  token = {}, spender = {}, error = {:#}", token_name(grant.token), grant.spender, e);
+                    continue;
+                }
+            };
+            if !live.is_live() {
+                revoked += 1;
+                continue;
+            }
+            let spender_name = spender_name(&known, grant.spender);
+            println!(
+                "  {}token = {}, spender = {} ({}), allowance = {}",
+                if spender_name.is_none() { "WARNING: unknown spender, " } else { "" },
+                token_name(grant.token),
+                spender_name.unwrap_or("unknown"),
+                grant.spender,
+                live.describe(grant.token)
+            );
+            let selected = match revoke_spender {
+                Some(spender) => grant.spender == spender,
+                None => spender_name.is_none(),
+            };
+            if selected {
+                to_revoke.push((grant.clone(), *signature_type));
+            }
+        }
+        println!("  revoked or spent = {}", revoked);
+    }
+
+    if !revoke {
+        if !to_revoke.is_empty() {
+            println!("{} approvals can be revoked with --revoke", to_revoke.len());
+        }
+        return Ok(());
+    }
+    if to_revoke.is_empty() {
+        println!("Nothing to revoke.");
+        return Ok(());
+    }
+
+    let signer = signer.context("Need PRIVATE_KEY to revoke approvals")?;
+    let owner = signer.address();
+    if !yes && !confirm(&format!("Revoke {} approvals?", to_revoke.len()))? {
+        println!("Aborted.");
+        return Ok(());
+    }
+
+    let provider = ProviderBuilder::new()
+        .wallet(signer.clone())
+        .connect_client(rpc::connect(rpc_urls).await?);
+    for (grant, signature_type) in to_revoke {
+        let Some(signature_type) = signature_type else {
+            println!("  token = {}, spender = {}, not one of this key's wallets, skipping", token_name(grant.token), grant.spender);
+            continue;
+        };
+        match send_call(&provider, owner, signature_type, grant.token, revoke_calldata(&grant)).await {
+            Ok(tx_hash) => println!("  token = {}, spender = {}, tx = {}, revoked", token_name(grant.token), grant.spender, tx_hash),
+            Err(e) => eprintln!("  token = {}, spender = {}, error = {:?}, revoke failed", token_name(grant.token), grant.spender, e),
+        }
+    }
+
+    Ok(())
+}
+
+/// The EOA and its proxy and Safe wallets, with the signature type that sends from each.
+fn own_wallets(owner: Address) -> Result<Vec<(&'static str, Address, Option<SignatureType>)>> {
+    Ok(vec![
+        ("EOA", owner, Some(SignatureType::Eoa)),
+        ("Proxy", funder_address(owner, SignatureType::Proxy)?, Some(SignatureType::Proxy)),
+        ("Safe", funder_address(owner, SignatureType::GnosisSafe)?, Some(SignatureType::GnosisSafe)),
+    ])
+}
+
+/// Spenders Polymarket trading needs: the approval targets plus the Conditional Tokens contract.
+fn known_spenders() -> Result<Vec<(&'static str, Address)>> {
+    let mut spenders = build_approval_targets(POLYGON)?;
+    let config = contract_config(POLYGON, false).context("Failed to get contract config")?;
+    spenders.push(("Conditional Tokens", config.conditional_tokens));
+    Ok(spenders)
+}
+
+fn spender_name(known: &[(&'static str, Address)], spender: Address) -> Option<&'static str> {
+    known.iter().find(|(_, address)| *address == spender).map(|(name, _)| *name)
+}
+
+fn token_name(token: Address) -> String {
+    let ctf = contract_config(POLYGON, false).map(|c| c.conditional_tokens);
+    match token {
+        USDC_E_ADDRESS => "USDC.e".to_string(),
+        USDC_NATIVE_ADDRESS => "USDC (Native)".to_string(),
+        t if Some(t) == ctf => "Conditional Tokens".to_string(),
+        t => t.to_string(),
+    }
+}
+
+/// What an approval grants: an ERC-20 allowance or an operator over all of an owner's tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrantKind {
+    Allowance,
+    Operator,
+}
+
+/// A `(owner, token, spender)` approval seen in the logs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grant {
+    pub owner: Address,
+    pub token: Address,
+    pub spender: Address,
+    pub kind: GrantKind,
+}
+
+/// The current on-chain value of a grant.
+pub enum GrantState {
+    Allowance(U256),
+    Operator(bool),
+}
+
+impl GrantState {
+    pub fn is_live(&self) -> bool {
+        match self {
+            GrantState::Allowance(allowance) => !allowance.is_zero(),
+            GrantState::Operator(approved) => *approved,
+        }
+    }
+
+    fn describe(&self, token: Address) -> String {
+        match self {
+            GrantState::Allowance(allowance) if is_unlimited_allowance(*allowance) => "unlimited".to_string(),
+            GrantState::Allowance(allowance) if token == USDC_E_ADDRESS || token == USDC_NATIVE_ADDRESS => {
+                format_units(*allowance, 6)
+            }
+            GrantState::Allowance(allowance) => format!("{} raw units", allowance),
+            GrantState::Operator(_) => "all tokens".to_string(),
+        }
+    }
+}
+
+/// Find every spender the owners ever approved from ERC-20 `Approval` and ERC-1155 `ApprovalForAll` logs.
+async fn scan_grants<P: Provider>(
+    provider: &P,
+    owners: &[Address],
+    from_block: u64,
+    to_block: u64,
+    chunk_size: u64,
+) -> Result<Vec<Grant>> {
+    let topics: Vec<B256> = owners.iter().map(|a| a.into_word()).collect();
+    // Both events index the owner first, so one scan finds them all
+    let filter = Filter::new()
+        .event_signature(vec![IERC20::Approval::SIGNATURE_HASH, IERC1155::ApprovalForAll::SIGNATURE_HASH])
+        .topic1(topics);
+    let logs = rpc::get_logs_chunked(provider, &filter, from_block, to_block, chunk_size).await?;
+
+    let grants = logs.iter().filter_map(grant_from_log).collect();
+    Ok(dedupe_grants(grants))
+}
+
+/// Decode an approval log. ERC-721 `Approval` shares the ERC-20 signature but indexes the
+/// token ID, so it fails to decode and is skipped.
+pub fn grant_from_log(log: &Log) -> Option<Grant> {
+    if let Ok(event) = log.log_decode::<IERC20::Approval>() {
+        return Some(Grant {
+            owner: event.inner.data.owner,
+            token: log.address(),
+            spender: event.inner.data.spender,
+            kind: GrantKind::Allowance,
+        });
+    }
+    let event = log.log_decode::<IERC1155::ApprovalForAll>().ok()?;
+    Some(Grant {
+        owner: event.inner.data.account,
+        token: log.address(),
+        spender: event.inner.data.operator,
+        kind: GrantKind::Operator,
+    })
+}
+
+/// Keep one entry per `(owner, token, spender, kind)`, in first-seen order.
+pub fn dedupe_grants(grants: Vec<Grant>) -> Vec<Grant> {
+    let mut unique: Vec<Grant> = Vec::new();
+    for grant in grants {
+        if !unique.contains(&grant) {
+            unique.push(grant);
+        }
+    }
+    unique
+}
+
+/// Read the current allowance or operator approval of every grant in one Multicall3 round-trip.
+async fn read_grant_states<P: Provider>(provider: &P, grants: &[Grant]) -> Result<Vec<Result<GrantState>>> {
+    let calls = grants
+        .iter()
+        .map(|g| match g.kind {
+            GrantKind::Allowance => call3(g.token, IERC20::allowanceCall { owner: g.owner, spender: g.spender }),
+            GrantKind::Operator => call3(g.token, IERC1155::isApprovedForAllCall { account: g.owner, operator: g.spender }),
+        })
+        .collect();
+    let results = aggregate3(provider, calls).await?;
+    Ok(grants
+        .iter()
+        .zip(&results)
+        .map(|(g, result)| match g.kind {
+            GrantKind::Allowance => decode_result::<IERC20::allowanceCall>(result).map(GrantState::Allowance),
+            GrantKind::Operator => decode_result::<IERC1155::isApprovedForAllCall>(result).map(GrantState::Operator),
+        })
+        .collect())
+}
+
+/// `approve(spender, 0)` or `setApprovalForAll(operator, false)`.
+pub fn revoke_calldata(grant: &Grant) -> Bytes {
+    match grant.kind {
+        GrantKind::Allowance => IERC20::approveCall { spender: grant.spender, value: U256::ZERO }.abi_encode().into(),
+        GrantKind::Operator => IERC1155::setApprovalForAllCall { operator: grant.spender, approved: false }
+            .abi_encode()
+            .into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::{Log as PrimitiveLog, LogData};
+
+    const OWNER: Address = Address::repeat_byte(0x11);
+    const TOKEN: Address = Address::repeat_byte(0x22);
+    const SPENDER: Address = Address::repeat_byte(0x33);
+
+    fn grant(kind: GrantKind) -> Grant {
+        Grant { owner: OWNER, token: TOKEN, spender: SPENDER, kind }
+    }
+
+    fn rpc_log(topics: Vec<B256>, data: Bytes) -> Log {
+        Log {
+            inner: PrimitiveLog { address: TOKEN, data: LogData::new_unchecked(topics, data) },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_grant_from_erc20_approval_log() {
+        let data = U256::MAX.to_be_bytes::<32>().to_vec().into();
+        let log = rpc_log(vec![IERC20::Approval::SIGNATURE_HASH, OWNER.into_word(), SPENDER.into_word()], data);
+        assert_eq!(grant_from_log(&log), Some(grant(GrantKind::Allowance)));
+    }
+
+    #[test]
+    fn test_grant_from_approval_for_all_log() {
+        let data = U256::from(1).to_be_bytes::<32>().to_vec().into();
+        let log = rpc_log(vec![IERC1155::ApprovalForAll::SIGNATURE_HASH, OWNER.into_word(), SPENDER.into_word()], data);
+        assert_eq!(grant_from_log(&log), Some(grant(GrantKind::Operator)));
+    }
+
+    #[test]
+    fn test_grant_from_erc721_approval_log_is_skipped() {
+        let topics = vec![IERC20::Approval::SIGNATURE_HASH, OWNER.into_word(), SPENDER.into_word(), B256::with_last_byte(7)];
+        assert_eq!(grant_from_log(&rpc_log(topics, Bytes::new())), None);
+    }
+
+    #[test]
+    fn test_dedupe_grants() {
+        let grants = vec![grant(GrantKind::Allowance), grant(GrantKind::Operator), grant(GrantKind::Allowance)];
+        assert_eq!(dedupe_grants(grants), vec![grant(GrantKind::Allowance), grant(GrantKind::Operator)]);
+    }
+
+    #[test]
+    fn test_grant_state_is_live() {
+        assert!(GrantState::Allowance(U256::from(1)).is_live());
+        assert!(!GrantState::Allowance(U256::ZERO).is_live());
+        assert!(GrantState::Operator(true).is_live());
+        assert!(!GrantState::Operator(false).is_live());
+    }
+
+    #[test]
+    fn test_revoke_calldata() {
+        let decoded = IERC20::approveCall::abi_decode(&revoke_calldata(&grant(GrantKind::Allowance))).unwrap();
+        assert_eq!(decoded.spender, SPENDER);
+        assert!(decoded.value.is_zero());
+
+        let decoded = IERC1155::setApprovalForAllCall::abi_decode(&revoke_calldata(&grant(GrantKind::Operator))).unwrap();
+        assert_eq!(decoded.operator, SPENDER);
+        assert!(!decoded.approved);
+    }
+}
@@ -1,6 +1,8 @@
 use anyhow::{Context, Result};
-use alloy::primitives::U256;
-use alloy::providers::ProviderBuilder;
+use alloy::primitives::{Bytes, U256};
+use alloy::providers::{Provider, ProviderBuilder};
+use alloy::rpc::types::TransactionRequest;
+use alloy::sol_types::SolCall;
 use polymarket_client_sdk::{
     POLYGON, PRIVATE_KEY_VAR,
     auth::{LocalSigner, Signer},
@@ -12,24 +14,21 @@ use std::str::FromStr;
 use std::time::Duration;
 use tokio::time::sleep;
 
-use crate::constants::{RPC_URL, USDC_E_ADDRESS, USDC_NATIVE_ADDRESS};
+use crate::constants::{USDC_E_ADDRESS, USDC_NATIVE_ADDRESS};
 use crate::contracts::{
+    IERC20, IERC1155,
     new_erc20, new_erc1155,
-    check_allowance, check_approval_for_all,
+    call3, aggregate3, decode_result,
     approve_token, set_approval_for_all,
 };
+use crate::rpc;
 
-pub async fn execute(dry_run: bool) -> Result<()> {
+pub async fn execute(dry_run: bool, rpc_urls: &[String]) -> Result<()> {
     let chain = POLYGON;
     let targets = build_approval_targets(chain)?;
 
     if dry_run {
-        println!("mode = \"dry_run\", showing approvals without executing");
-        for (name, target) in &targets {
-            println!("contract = {}, address = {}, would receive approval", name, target);
-        }
-        println!("total = {}, contracts would be approved", targets.len());
-        return Ok(());
+        return execute_dry_run(chain, &targets, rpc_urls).await;
     }
 
     let private_key = env::var(PRIVATE_KEY_VAR).context("Need PRIVATE_KEY environment variable")?;
@@ -37,8 +36,7 @@ pub async fn execute(dry_run: bool) -> Result<()> {
 
     let provider = ProviderBuilder::new()
         .wallet(signer.clone())
-        .connect(RPC_URL)
-        .await?;
+        .connect_client(rpc::connect(rpc_urls).await?);
 
     let owner = signer.address();
     println!("wallet loaded: {}", owner);
@@ -48,80 +46,279 @@ pub async fn execute(dry_run: bool) -> Result<()> {
 
     println!("phase = \"checking\", querying current allowances");
 
-    for (name, target) in &targets {
-        let tokens = [
-            ("USDC.e", new_erc20(USDC_E_ADDRESS, provider.clone())),
-            ("USDC (Native)", new_erc20(USDC_NATIVE_ADDRESS, provider.clone())),
-        ];
+    let pending = plan_approvals(&provider, owner, config.conditional_tokens, &targets).await?;
+
+    println!("phase = \"approving\", setting approvals");
+
+    for approval in &pending {
+        println!("contract = {}, token = {}, address = {}, approving", approval.contract, approval.token, approval.spender);
 
-        for (token_name, token_contract) in &tokens {
-            match check_allowance(token_contract, owner, *target).await {
-                Ok(allowance) => println!("contract = {}, token = {}, allowance = {}", name, token_name, allowance),
-                Err(e) => eprintln!("contract = {}, token = {}, error = {:?}, failed to check allowance", name, token_name, e),
+        println!("Waiting 10s...");
+        sleep(Duration::from_secs(10)).await;
+
+        match approval.kind {
+            ApprovalKind::Erc20 => {
+                let token_contract = new_erc20(approval.to, provider.clone());
+                match approve_token(&token_contract, approval.spender, U256::MAX).await {
+                    Ok(tx_hash) => println!("contract = {}, token = {}, tx = {}, approved", approval.contract, approval.token, tx_hash),
+                    Err(e) => eprintln!("contract = {}, token = {}, error = {:?}, approve failed", approval.contract, approval.token, e),
+                }
+            }
+            ApprovalKind::Ctf => {
+                match set_approval_for_all(&ctf, approval.spender, true).await {
+                    Ok(tx_hash) => println!("contract = {}, tx = {}, CTF approved", approval.contract, tx_hash),
+                    Err(e) => eprintln!("contract = {}, error = {:?}, CTF setApprovalForAll failed", approval.contract, e),
+                }
             }
         }
+    }
 
-        match check_approval_for_all(&ctf, owner, *target).await {
-            Ok(approved) => println!("contract = {}, ctf_approved = {}", name, approved),
-            Err(e) => eprintln!("contract = {}, error = {:?}, failed to check CTF approval", name, e),
+    println!("phase = \"verifying\", confirming approvals");
+
+    let states = read_approval_states(&provider, owner, config.conditional_tokens, &targets).await?;
+    for state in &states {
+        for (token_name, _, allowance) in &state.allowances {
+            match allowance {
+                Ok(allowance) => println!("contract = {}, token = {}, allowance = {}, verified", state.contract, token_name, allowance),
+                Err(e) => eprintln!("contract = {}, token = {}, error = {:?}, verification failed", state.contract, token_name, e),
+            }
+        }
+
+        match &state.ctf_approved {
+            Ok(approved) => println!("contract = {}, ctf_approved = {}, verified", state.contract, approved),
+            Err(e) => eprintln!("contract = {}, error = {:?}, verification failed", state.contract, e),
         }
     }
 
-    println!("phase = \"approving\", setting approvals");
+    println!("all approvals complete");
 
-    for (name, target) in &targets {
-        println!("contract = {}, address = {}, approving", name, target);
+    Ok(())
+}
 
-        println!("Waiting 10s...");
-        sleep(Duration::from_secs(10)).await;
+/// Query chain state and print the approval transactions that a live run would send,
+/// with calldata and estimated gas cost. Nothing is signed or broadcast.
+async fn execute_dry_run(chain: u64, targets: &[(&'static str, Address)], rpc_urls: &[String]) -> Result<()> {
+    println!("mode = \"dry_run\", querying chain state without executing");
 
-        let tokens = [
-            ("USDC.e", new_erc20(USDC_E_ADDRESS, provider.clone())),
-            ("USDC (Native)", new_erc20(USDC_NATIVE_ADDRESS, provider.clone())),
-        ];
+    let owner = resolve_owner()?;
+    println!("wallet = {}", owner);
 
-        for (token_name, token_contract) in &tokens {
-            match approve_token(token_contract, *target, U256::MAX).await {
-                Ok(tx_hash) => println!("contract = {}, token = {}, tx = {}, approved", name, token_name, tx_hash),
-                Err(e) => eprintln!("contract = {}, token = {}, error = {:?}, approve failed", name, token_name, e),
+    let provider = ProviderBuilder::new().connect_client(rpc::connect(rpc_urls).await?);
+    let config = contract_config(chain, false).context("Failed to get contract config")?;
+
+    println!("phase = \"checking\", querying current allowances");
+
+    let pending = plan_approvals(&provider, owner, config.conditional_tokens, targets).await?;
+
+    if pending.is_empty() {
+        println!("total = 0, all approvals already in place");
+        return Ok(());
+    }
+
+    println!("phase = \"estimating\", estimating gas for pending transactions");
+
+    let gas_price = provider.get_gas_price().await.context("Failed to fetch gas price")?;
+    println!("gas_price = {} gwei", format_units(U256::from(gas_price), 9));
+
+    let mut total_gas: u64 = 0;
+    let mut failed_estimates = 0;
+    for approval in &pending {
+        println!(
+            "contract = {}, token = {}, to = {}, spender = {}, would send",
+            approval.contract, approval.token, approval.to, approval.spender
+        );
+        println!("  calldata = {}", approval.calldata);
+
+        let tx = TransactionRequest::default()
+            .from(owner)
+            .to(approval.to)
+            .input(approval.calldata.clone().into());
+
+        match provider.estimate_gas(tx).await {
+            Ok(gas) => {
+                total_gas += gas;
+                let cost = U256::from(gas) * U256::from(gas_price);
+                println!("  gas = {}, cost = {} POL", gas, format_units(cost, 18));
+            }
+            Err(e) => {
+                failed_estimates += 1;
+                eprintln!("  error = {:?}, gas estimation failed", e);
             }
-            println!("Waiting 10s...");
-            sleep(Duration::from_secs(10)).await;
         }
+    }
 
-        println!("Waiting 10s...");
-        sleep(Duration::from_secs(10)).await;
+    let total_cost = U256::from(total_gas) * U256::from(gas_price);
+    println!(
+        "total = {}, transactions would be sent, gas = {}, cost = {} POL{}",
+        pending.len(),
+        total_gas,
+        format_units(total_cost, 18),
+        partial_total_note(failed_estimates)
+    );
+
+    Ok(())
+}
+
+/// Note marking the dry-run totals as partial when some gas estimates failed.
+fn partial_total_note(failed_estimates: usize) -> String {
+    match failed_estimates {
+        0 => String::new(),
+        n => format!(" (partial, {} estimates failed)", n),
+    }
+}
+
+/// What kind of approval a pending transaction grants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApprovalKind {
+    /// ERC-20 `approve(spender, MAX)` on a collateral token.
+    Erc20,
+    /// ERC-1155 `setApprovalForAll(operator, true)` on the Conditional Tokens contract.
+    Ctf,
+}
 
-        match set_approval_for_all(&ctf, *target, true).await {
-            Ok(tx_hash) => println!("contract = {}, tx = {}, CTF approved", name, tx_hash),
-            Err(e) => eprintln!("contract = {}, error = {:?}, CTF setApprovalForAll failed", name, e),
+/// An approval transaction that still needs to be sent.
+#[derive(Debug, Clone)]
+pub struct PendingApproval {
+    pub contract: &'static str,
+    pub token: &'static str,
+    pub kind: ApprovalKind,
+    pub to: Address,
+    pub spender: Address,
+    pub calldata: Bytes,
+}
+
+/// Current approval state of one target contract, as read from chain.
+pub struct ApprovalState {
+    pub contract: &'static str,
+    pub spender: Address,
+    /// `(token name, token address, allowance)` for each collateral token.
+    pub allowances: Vec<(&'static str, Address, Result<U256>)>,
+    pub ctf_approved: Result<bool>,
+}
+
+/// Read every target's collateral allowances and CTF approval in a single Multicall3 round-trip.
+pub async fn read_approval_states<P: Provider>(
+    provider: &P,
+    owner: Address,
+    conditional_tokens: Address,
+    targets: &[(&'static str, Address)],
+) -> Result<Vec<ApprovalState>> {
+    let tokens = [
+        ("USDC.e", USDC_E_ADDRESS),
+        ("USDC (Native)", USDC_NATIVE_ADDRESS),
+    ];
+
+    let mut calls = Vec::new();
+    for (_, target) in targets {
+        for (_, token_address) in tokens {
+            calls.push(call3(token_address, IERC20::allowanceCall { owner, spender: *target }));
         }
+        calls.push(call3(conditional_tokens, IERC1155::isApprovedForAllCall { account: owner, operator: *target }));
     }
 
-    println!("phase = \"verifying\", confirming approvals");
+    let results = aggregate3(provider, calls).await?;
+    let mut results = results.iter();
 
-    for (name, target) in &targets {
-        let tokens = [
-            ("USDC.e", new_erc20(USDC_E_ADDRESS, provider.clone())),
-            ("USDC (Native)", new_erc20(USDC_NATIVE_ADDRESS, provider.clone())),
-        ];
+    let mut states = Vec::new();
+    for (name, target) in targets {
+        let mut allowances = Vec::new();
+        for (token_name, token_address) in tokens {
+            let allowance = results.next().context("Missing multicall result")?;
+            allowances.push((token_name, token_address, decode_result::<IERC20::allowanceCall>(allowance)));
+        }
+        let approved = results.next().context("Missing multicall result")?;
+        states.push(ApprovalState {
+            contract: name,
+            spender: *target,
+            allowances,
+            ctf_approved: decode_result::<IERC1155::isApprovedForAllCall>(approved),
+        });
+    }
+
+    Ok(states)
+}
 
-        for (token_name, token_contract) in &tokens {
-            match check_allowance(token_contract, owner, *target).await {
-                Ok(allowance) => println!("contract = {}, token = {}, allowance = {}, verified", name, token_name, allowance),
-                Err(e) => eprintln!("contract = {}, token = {}, error = {:?}, verification failed", name, token_name, e),
+/// Read the current allowances and CTF approvals for every target, printing each one,
+/// and return the transactions needed to bring them all up to date.
+async fn plan_approvals<P: Provider>(
+    provider: &P,
+    owner: Address,
+    conditional_tokens: Address,
+    targets: &[(&'static str, Address)],
+) -> Result<Vec<PendingApproval>> {
+    let states = read_approval_states(provider, owner, conditional_tokens, targets).await?;
+
+    for state in &states {
+        for (token_name, _, allowance) in &state.allowances {
+            match allowance {
+                Ok(allowance) => println!("contract = {}, token = {}, allowance = {}", state.contract, token_name, allowance),
+                Err(e) => eprintln!("contract = {}, token = {}, error = {:?}, failed to check allowance", state.contract, token_name, e),
+            }
+        }
+        match &state.ctf_approved {
+            Ok(approved) => println!("contract = {}, ctf_approved = {}", state.contract, approved),
+            Err(e) => eprintln!("contract = {}, error = {:?}, failed to check CTF approval", state.contract, e),
+        }
+    }
+
+    Ok(pending_approvals(&states, conditional_tokens))
+}
+
+/// The transactions needed to bring every target up to date.
+/// Reads that failed are treated as missing approvals.
+pub fn pending_approvals(states: &[ApprovalState], conditional_tokens: Address) -> Vec<PendingApproval> {
+    let mut pending = Vec::new();
+
+    for state in states {
+        for (token_name, token_address, allowance) in &state.allowances {
+            if !allowance.as_ref().is_ok_and(|a| is_unlimited_allowance(*a)) {
+                pending.push(PendingApproval {
+                    contract: state.contract,
+                    token: token_name,
+                    kind: ApprovalKind::Erc20,
+                    to: *token_address,
+                    spender: state.spender,
+                    calldata: IERC20::approveCall { spender: state.spender, value: U256::MAX }.abi_encode().into(),
+                });
             }
         }
 
-        match check_approval_for_all(&ctf, owner, *target).await {
-            Ok(approved) => println!("contract = {}, ctf_approved = {}, verified", name, approved),
-            Err(e) => eprintln!("contract = {}, error = {:?}, verification failed", name, e),
+        if !state.ctf_approved.as_ref().is_ok_and(|approved| *approved) {
+            pending.push(PendingApproval {
+                contract: state.contract,
+                token: "CTF",
+                kind: ApprovalKind::Ctf,
+                to: conditional_tokens,
+                spender: state.spender,
+                calldata: IERC1155::setApprovalForAllCall { operator: state.spender, approved: true }.abi_encode().into(),
+            });
         }
     }
 
-    println!("all approvals complete");
+    pending
+}
 
-    Ok(())
+/// Resolve the approving wallet without requiring a signer:
+/// the private key's address if set, otherwise `USER_ADDRESS`.
+fn resolve_owner() -> Result<Address> {
+    if let Ok(private_key) = env::var(PRIVATE_KEY_VAR) {
+        let signer = LocalSigner::from_str(&private_key).context("Invalid private key")?;
+        return Ok(signer.address());
+    }
+    let user = env::var("USER_ADDRESS").context("PRIVATE_KEY or USER_ADDRESS env var not set")?;
+    Address::from_str(&user).context("Invalid address format in USER_ADDRESS")
+}
+
+/// Whether an ERC-20 allowance is large enough to count as an unlimited approval.
+/// Spending from a `MAX` approval decrements it on some tokens, so anything
+/// above half of `U256::MAX` is treated as unlimited.
+pub fn is_unlimited_allowance(allowance: U256) -> bool {
+    allowance >= U256::MAX >> 1
+}
+
+/// Format a raw integer amount with the given number of decimals.
+pub fn format_units(raw: U256, decimals: u8) -> String {
+    alloy::primitives::utils::format_units(raw, decimals).unwrap_or_else(|_| raw.to_string())
 }
 
 /// Build the list of contracts that need token approvals.
@@ -171,4 +368,52 @@ mod tests {
             assert_ne!(*addr, Address::ZERO, "{} should have a non-zero address", name);
         }
     }
+
+    #[test]
+    fn test_is_unlimited_allowance() {
+        assert!(is_unlimited_allowance(U256::MAX));
+        assert!(is_unlimited_allowance(U256::MAX - U256::from(1_000_000u64)));
+        assert!(!is_unlimited_allowance(U256::ZERO));
+        assert!(!is_unlimited_allowance(U256::from(1_000_000_000u64)));
+    }
+
+    #[test]
+    fn test_format_units_pol() {
+        // 21000 gas at 30 gwei = 0.00063 POL
+        let cost = U256::from(21_000u64) * U256::from(30_000_000_000u64);
+        assert_eq!(format_units(cost, 18), "0.000630000000000000");
+    }
+
+    #[test]
+    fn test_partial_total_note() {
+        assert_eq!(partial_total_note(0), "");
+        assert_eq!(partial_total_note(2), " (partial, 2 estimates failed)");
+    }
+
+    #[test]
+    fn test_format_units_gwei() {
+        assert_eq!(format_units(U256::from(30_000_000_000u64), 9), "30.000000000");
+    }
+
+    #[test]
+    fn test_pending_approvals() {
+        let spender = Address::repeat_byte(1);
+        let ctf = Address::repeat_byte(2);
+        let state = |allowance: Result<U256>, ctf_approved: Result<bool>| ApprovalState {
+            contract: "CTF Exchange",
+            spender,
+            allowances: vec![("USDC.e", USDC_E_ADDRESS, allowance)],
+            ctf_approved,
+        };
+
+        assert!(pending_approvals(&[state(Ok(U256::MAX), Ok(true))], ctf).is_empty());
+
+        let pending = pending_approvals(&[state(Ok(U256::ZERO), Ok(true))], ctf);
+        assert_eq!(pending.len(), 1);
+        assert_eq!((pending[0].kind, pending[0].to, pending[0].spender), (ApprovalKind::Erc20, USDC_E_ADDRESS, spender));
+
+        let pending = pending_approvals(&[state(Err(anyhow::anyhow!("reverted")), Err(anyhow::anyhow!("reverted")))], ctf);
+        assert_eq!(pending.iter().map(|p| p.kind).collect::<Vec<_>>(), [ApprovalKind::Erc20, ApprovalKind::Ctf]);
+        assert_eq!(pending[1].to, ctf);
+    }
 }
@@ -0,0 +1,191 @@
+use anyhow::{Context, Result};
+use alloy::primitives::U256;
+use alloy::providers::{Provider, ProviderBuilder};
+use polymarket_client_sdk::types::Address;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::commands::positions::resolve_user_address;
+use crate::commands::status::format_token_amount;
+use crate::contracts::{IERC20, call3, aggregate3, decode_result};
+use crate::rpc;
+
+pub async fn execute(tokens: Vec<String>, user: Option<String>, rpc_urls: &[String]) -> Result<()> {
+    let account = resolve_user_address(user)?;
+    let tokens = tokens
+        .iter()
+        .map(|t| Address::from_str(t).with_context(|| format!("Invalid token address: {}", t)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let provider = ProviderBuilder::new().connect_client(rpc::connect(rpc_urls).await?);
+
+    let mut cache = TokenCache::load();
+    let balances = fetch_token_balances(&provider, &mut cache, account, &tokens).await?;
+    if let Err(e) = cache.save() {
+        log::warn!("error = {:#}, failed to save token metadata cache", e);
+    }
+
+    println!("Balances for {}:", account);
+    for (token, metadata, balance) in &balances {
+        match balance {
+            Ok(raw) => match format_token_amount(*raw, metadata.decimals) {
+                Some(amount) => println!("- {}: {} ({})", metadata.symbol, amount.normalize(), token),
+                None => println!("- {}: {} raw units ({})", metadata.symbol, raw, token),
+            },
+            Err(e) => println!("- {}: error ({}) ({})", metadata.symbol, e, token),
+        }
+    }
+
+    Ok(())
+}
+
+/// On-chain ERC-20 metadata needed to format balances.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenMetadata {
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+/// Fetch balances for each token, reading `decimals()` and `symbol()` only for tokens missing
+/// from the cache. Everything goes out in one Multicall3 round-trip.
+pub async fn fetch_token_balances<P: Provider>(
+    provider: &P,
+    cache: &mut TokenCache,
+    account: Address,
+    tokens: &[Address],
+) -> Result<Vec<(Address, TokenMetadata, Result<U256>)>> {
+    let missing: Vec<Address> = tokens.iter().copied().filter(|t| cache.get(t).is_none()).collect();
+
+    let mut calls = Vec::new();
+    for token in tokens {
+        calls.push(call3(*token, IERC20::balanceOfCall { account }));
+    }
+    for token in &missing {
+        calls.push(call3(*token, IERC20::decimalsCall {}));
+        calls.push(call3(*token, IERC20::symbolCall {}));
+    }
+
+    let results = aggregate3(provider, calls).await?;
+    let (balance_results, metadata_results) = results.split_at(tokens.len());
+
+    for (token, pair) in missing.iter().zip(metadata_results.chunks(2)) {
+        let decimals = decode_result::<IERC20::decimalsCall>(&pair[0])
+            .with_context(|| format!("Failed to read decimals() of {}; is it an ERC-20 token?", token))?;
+        // symbol() is optional in ERC-20, fall back to the address when it's missing
+        let symbol = decode_result::<IERC20::symbolCall>(&pair[1]).unwrap_or_else(|_| token.to_string());
+        cache.insert(*token, TokenMetadata { symbol, decimals });
+    }
+
+    let mut balances = Vec::new();
+    for (token, result) in tokens.iter().zip(balance_results) {
+        let metadata = cache.get(token).cloned().context("Missing token metadata")?;
+        balances.push((*token, metadata, decode_result::<IERC20::balanceOfCall>(result)));
+    }
+
+    Ok(balances)
+}
+
+/// Token metadata cached on disk per token address. Decimals and symbols never change,
+/// so entries don't expire.
+#[derive(Debug, Default)]
+pub struct TokenCache {
+    path: Option<PathBuf>,
+    tokens: HashMap<Address, TokenMetadata>,
+    dirty: bool,
+}
+
+impl TokenCache {
+    /// Load the cache from the default location. A missing or unreadable file gives an empty cache.
+    pub fn load() -> Self {
+        match cache_path() {
+            Some(path) => Self::load_from(path),
+            None => Self::default(),
+        }
+    }
+
+    pub fn load_from(path: PathBuf) -> Self {
+        let tokens = fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        Self { path: Some(path), tokens, dirty: false }
+    }
+
+    pub fn get(&self, token: &Address) -> Option<&TokenMetadata> {
+        self.tokens.get(token)
+    }
+
+    pub fn insert(&mut self, token: Address, metadata: TokenMetadata) {
+        self.tokens.insert(token, metadata);
+        self.dirty = true;
+    }
+
+    /// Write the cache back to disk if anything was added.
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else { return Ok(()) };
+        if !self.dirty {
+            return Ok(());
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).context("Failed to create cache directory")?;
+        }
+        let json = serde_json::to_string_pretty(&self.tokens)?;
+        fs::write(path, json).context("Failed to write token cache")
+    }
+}
+
+/// `$XDG_CACHE_HOME/polymarket-cli/tokens.json`, falling back to `~/.cache`.
+fn cache_path() -> Option<PathBuf> {
+    let base = env::var("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|_| env::var("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .ok()?;
+    Some(base.join("polymarket-cli").join("tokens.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::USDC_E_ADDRESS;
+
+    fn usdc() -> TokenMetadata {
+        TokenMetadata { symbol: "USDC".to_string(), decimals: 6 }
+    }
+
+    #[test]
+    fn test_token_cache_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("tokens.json");
+
+        let mut cache = TokenCache::load_from(path.clone());
+        assert!(cache.get(&USDC_E_ADDRESS).is_none());
+        cache.insert(USDC_E_ADDRESS, usdc());
+        cache.save().unwrap();
+
+        let reloaded = TokenCache::load_from(path);
+        assert_eq!(reloaded.get(&USDC_E_ADDRESS), Some(&usdc()));
+    }
+
+    #[test]
+    fn test_token_cache_corrupt_file_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tokens.json");
+        fs::write(&path, "not json").unwrap();
+
+        let cache = TokenCache::load_from(path);
+        assert!(cache.get(&USDC_E_ADDRESS).is_none());
+    }
+
+    #[test]
+    fn test_token_cache_save_without_changes_writes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tokens.json");
+
+        TokenCache::load_from(path.clone()).save().unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,386 @@
+use anyhow::{Context, Result};
+use alloy::primitives::{B256, Bytes, U256};
+use alloy::providers::{Provider, ProviderBuilder};
+use alloy::sol_types::SolCall;
+use polymarket_client_sdk::{
+    POLYGON, PRIVATE_KEY_VAR,
+    auth::{LocalSigner, Signer},
+    clob::{Client as ClobClient, Config as ClobConfig},
+    types::Address,
+};
+use std::env;
+use std::str::FromStr;
+
+use crate::commands::split::SetContracts;
+use crate::commands::status::{format_balance, parse_balance};
+use crate::constants::USDC_E_ADDRESS;
+use crate::contracts::{IERC20, IERC1155, INegRiskAdapter, call3, aggregate3, decode_result};
+use crate::rpc;
+use crate::wallet::{funder_address, send_call, signature_type_from_env};
+
+/// Denominator of the adapter's per-market conversion fee.
+const FEE_BIPS_DENOMINATOR: u64 = 10_000;
+
+pub async fn execute(
+    markets: Vec<String>,
+    amount: Option<String>,
+    max: bool,
+    dry_run: bool,
+    rpc_urls: &[String],
+) -> Result<()> {
+    let requested = match (&amount, max) {
+        (Some(amount), false) => Some(parse_balance(amount)?),
+        (None, true) => None,
+        _ => anyhow::bail!("Pass either --amount or --max"),
+    };
+
+    let private_key = env::var(PRIVATE_KEY_VAR).context("Need PRIVATE_KEY environment variable")?;
+    let signer = LocalSigner::from_str(&private_key)?.with_chain_id(Some(POLYGON));
+    let owner = signer.address();
+    let signature_type = signature_type_from_env()?;
+    let holder = funder_address(owner, signature_type)?;
+    println!("wallet = {}, holder = {}, signature_type = {}", owner, holder, signature_type);
+
+    let (market_id, selected) = fetch_selected_questions(&markets).await?;
+    let indices: Vec<usize> = selected.iter().map(|(index, _)| *index).collect();
+    println!("event = {}, converting = {} no positions", market_id, indices.len());
+
+    let provider = ProviderBuilder::new()
+        .wallet(signer.clone())
+        .connect_client(rpc::connect(rpc_urls).await?);
+
+    let contracts = SetContracts::polygon()?;
+    let adapter = contracts.neg_risk_adapter;
+
+    let results = aggregate3(&provider, vec![
+        call3(adapter, INegRiskAdapter::getQuestionCountCall { marketId: market_id }),
+        call3(adapter, INegRiskAdapter::getFeeBipsCall { marketId: market_id }),
+        call3(contracts.conditional_tokens, IERC1155::isApprovedForAllCall { account: holder, operator: adapter }),
+    ])
+    .await?;
+    let question_count: usize = decode_result::<INegRiskAdapter::getQuestionCountCall>(&results[0])
+        .context("Failed to read question count")?
+        .try_into()
+        .context("Question count out of range")?;
+    let fee_bips = decode_result::<INegRiskAdapter::getFeeBipsCall>(&results[1]).context("Failed to read fee")?;
+    let approved = decode_result::<IERC1155::isApprovedForAllCall>(&results[2]).context("Failed to read CTF approval")?;
+
+    if let Some(index) = indices.iter().find(|i| **i >= question_count) {
+        anyhow::bail!("Question index {} is outside the event's {} questions", index, question_count);
+    }
+
+    let position_ids = read_position_ids(&provider, adapter, market_id, question_count).await?;
+    let before = read_holdings(&provider, contracts.conditional_tokens, holder, &position_ids).await?;
+
+    let convertible = indices.iter().map(|i| before.no[*i]).min().unwrap_or_default();
+    let amount = requested.unwrap_or(convertible);
+    anyhow::ensure!(!amount.is_zero(), "No No positions to convert in the selected markets");
+    anyhow::ensure!(
+        amount <= convertible,
+        "Not enough No positions: can convert at most {}, asked for {}",
+        format_balance(convertible),
+        format_balance(amount)
+    );
+
+    let expected = preview_conversion(&before, &indices, amount, fee_bips);
+    println!("amount = {}, fee_bips = {}", format_balance(amount), fee_bips);
+    print_changes(&before, &expected, &selected);
+
+    if !approved {
+        let data: Bytes = IERC1155::setApprovalForAllCall { operator: adapter, approved: true }.abi_encode().into();
+        if dry_run {
+            println!("would approve {} for outcome tokens", adapter);
+        } else {
+            let tx_hash = send_call(&provider, owner, signature_type, contracts.conditional_tokens, data).await?;
+            println!("tx = {}, approved {} for outcome tokens", tx_hash, adapter);
+        }
+    }
+
+    if dry_run {
+        println!("would convert via Neg Risk Adapter");
+        return Ok(());
+    }
+
+    let data = convert_calldata(market_id, &indices, amount);
+    let tx_hash = send_call(&provider, owner, signature_type, adapter, data).await?;
+    println!("tx = {}, converted", tx_hash);
+
+    let after = read_holdings(&provider, contracts.conditional_tokens, holder, &position_ids).await?;
+    let mismatches = holdings_mismatches(&expected, &after);
+    if mismatches.is_empty() {
+        println!("holdings match the preview");
+    } else {
+        for mismatch in mismatches {
+            println!("WARNING: {}", mismatch);
+        }
+    }
+
+    Ok(())
+}
+
+/// Outcome token and collateral balances across every question of a neg-risk event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Holdings {
+    pub collateral: U256,
+    pub yes: Vec<U256>,
+    pub no: Vec<U256>,
+}
+
+/// Resolve the selected condition IDs to their neg-risk event and question indices.
+/// Every market has to belong to the same event.
+async fn fetch_selected_questions(markets: &[String]) -> Result<(B256, Vec<(usize, String)>)> {
+    let client = ClobClient::new("https://clob.polymarket.com", ClobConfig::default())?;
+    let mut market_id: Option<B256> = None;
+    let mut selected = Vec::new();
+    for condition_id in markets {
+        B256::from_str(condition_id).context("Invalid condition ID")?;
+        let market = client.market(condition_id).await.context("Failed to fetch market")?;
+        anyhow::ensure!(market.neg_risk, "Market {} is not a neg-risk market", condition_id);
+
+        let id = B256::from_str(&market.neg_risk_market_id).context("Invalid neg risk market ID")?;
+        match market_id {
+            Some(existing) if existing != id => anyhow::bail!("Markets belong to different neg-risk events"),
+            _ => market_id = Some(id),
+        }
+
+        let question_id = B256::from_str(&market.question_id).context("Invalid question ID")?;
+        let index = question_index(id, question_id)?;
+        anyhow::ensure!(!selected.iter().any(|(i, _)| *i == index), "Market {} given twice", condition_id);
+        selected.push((index, market.question));
+    }
+    let market_id = market_id.context("Pass at least one --market")?;
+    Ok((market_id, selected))
+}
+
+/// Read the Yes and No position IDs of every question in one Multicall3 round-trip.
+async fn read_position_ids<P: Provider>(
+    provider: &P,
+    adapter: Address,
+    market_id: B256,
+    question_count: usize,
+) -> Result<Vec<[U256; 2]>> {
+    let mut calls = Vec::new();
+    for index in 0..question_count {
+        let question_id = question_id(market_id, index);
+        for outcome in [true, false] {
+            calls.push(call3(adapter, INegRiskAdapter::getPositionIdCall { questionId: question_id, outcome }));
+        }
+    }
+    let results = aggregate3(provider, calls).await?;
+    results
+        .chunks(2)
+        .map(|pair| {
+            Ok([
+                decode_result::<INegRiskAdapter::getPositionIdCall>(&pair[0]).context("Failed to read position ID")?,
+                decode_result::<INegRiskAdapter::getPositionIdCall>(&pair[1]).context("Failed to read position ID")?,
+            ])
+        })
+        .collect()
+}
+
+/// Read USDC.e and every outcome token balance of the holder in one Multicall3 round-trip.
+async fn read_holdings<P: Provider>(
+    provider: &P,
+    conditional_tokens: Address,
+    holder: Address,
+    position_ids: &[[U256; 2]],
+) -> Result<Holdings> {
+    let mut calls = vec![call3(USDC_E_ADDRESS, IERC20::balanceOfCall { account: holder })];
+    for ids in position_ids {
+        for id in ids {
+            calls.push(call3(conditional_tokens, IERC1155::balanceOfCall { account: holder, id: *id }));
+        }
+    }
+    let results = aggregate3(provider, calls).await?;
+    let collateral = decode_result::<IERC20::balanceOfCall>(&results[0]).context("Failed to read USDC.e balance")?;
+    let mut holdings = Holdings { collateral, yes: Vec::new(), no: Vec::new() };
+    for pair in results[1..].chunks(2) {
+        holdings.yes.push(decode_result::<IERC1155::balanceOfCall>(&pair[0]).context("Failed to read outcome token balance")?);
+        holdings.no.push(decode_result::<IERC1155::balanceOfCall>(&pair[1]).context("Failed to read outcome token balance")?);
+    }
+    Ok(holdings)
+}
+
+fn print_changes(before: &Holdings, after: &Holdings, selected: &[(usize, String)]) {
+    for index in 0..before.yes.len() {
+        let name = selected
+            .iter()
+            .find(|(i, _)| *i == index)
+            .map(|(_, question)| question.as_str())
+            .unwrap_or("-");
+        if before.yes[index] == after.yes[index] && before.no[index] == after.no[index] {
+            continue;
+        }
+        println!(
+            "  question = {}, yes = {} -> {}, no = {} -> {}, market = {}",
+            index,
+            format_balance(before.yes[index]),
+            format_balance(after.yes[index]),
+            format_balance(before.no[index]),
+            format_balance(after.no[index]),
+            name
+        );
+    }
+    println!("  usdc = ${} -> ${}", format_balance(before.collateral), format_balance(after.collateral));
+}
+
+/// Question IDs of a neg-risk event are its market ID plus the question index.
+pub fn question_id(market_id: B256, index: usize) -> B256 {
+    B256::from(U256::from_be_bytes(market_id.0) + U256::from(index))
+}
+
+/// Inverse of [`question_id`].
+pub fn question_index(market_id: B256, question_id: B256) -> Result<usize> {
+    let market = U256::from_be_bytes(market_id.0);
+    let question = U256::from_be_bytes(question_id.0);
+    anyhow::ensure!(question >= market, "Question {} is not part of event {}", question_id, market_id);
+    // The adapter keeps question indices in a single byte.
+    let index: u8 = (question - market)
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("Question {} is not part of event {}", question_id, market_id))?;
+    Ok(index as usize)
+}
+
+/// Bitmask with one bit per converted question.
+pub fn index_set(indices: &[usize]) -> U256 {
+    indices.iter().fold(U256::ZERO, |acc, i| acc | (U256::from(1) << *i))
+}
+
+/// Holdings after converting `amount` No tokens of each selected question.
+///
+/// The adapter takes the No tokens, charges its fee, then pays out one Yes token of
+/// every other question plus `(selected - 1)` USDC.e per unit converted.
+pub fn preview_conversion(before: &Holdings, indices: &[usize], amount: U256, fee_bips: U256) -> Holdings {
+    let fee = amount * fee_bips / U256::from(FEE_BIPS_DENOMINATOR);
+    let amount_out = amount - fee;
+
+    let mut after = before.clone();
+    for index in 0..after.yes.len() {
+        if indices.contains(&index) {
+            after.no[index] = after.no[index].saturating_sub(amount);
+        } else {
+            after.yes[index] += amount_out;
+        }
+    }
+    if indices.len() > 1 {
+        after.collateral += amount_out * U256::from(indices.len() - 1);
+    }
+    after
+}
+
+/// Differences between the previewed and the actual holdings, one line each.
+pub fn holdings_mismatches(expected: &Holdings, actual: &Holdings) -> Vec<String> {
+    let mut mismatches = Vec::new();
+    if expected.collateral != actual.collateral {
+        mismatches.push(format!(
+            "usdc = ${}, expected = ${}",
+            format_balance(actual.collateral),
+            format_balance(expected.collateral)
+        ));
+    }
+    for (index, (expected_yes, actual_yes)) in expected.yes.iter().zip(&actual.yes).enumerate() {
+        if expected_yes != actual_yes {
+            mismatches.push(format!(
+                "question = {}, yes = {}, expected = {}",
+                index,
+                format_balance(*actual_yes),
+                format_balance(*expected_yes)
+            ));
+        }
+    }
+    for (index, (expected_no, actual_no)) in expected.no.iter().zip(&actual.no).enumerate() {
+        if expected_no != actual_no {
+            mismatches.push(format!(
+                "question = {}, no = {}, expected = {}",
+                index,
+                format_balance(*actual_no),
+                format_balance(*expected_no)
+            ));
+        }
+    }
+    mismatches
+}
+
+/// `convertPositions` on the Neg Risk Adapter.
+pub fn convert_calldata(market_id: B256, indices: &[usize], amount: U256) -> Bytes {
+    INegRiskAdapter::convertPositionsCall {
+        marketId: market_id,
+        indexSet: index_set(indices),
+        amount,
+    }
+    .abi_encode()
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MARKET_ID: &str = "0xe3b1bc389210504ebcb9cffe4b0ed06ccac50561e0f24abb6379984cec030f00";
+
+    fn holdings(collateral: u64, yes: &[u64], no: &[u64]) -> Holdings {
+        Holdings {
+            collateral: U256::from(collateral),
+            yes: yes.iter().map(|v| U256::from(*v)).collect(),
+            no: no.iter().map(|v| U256::from(*v)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_question_id_roundtrip() {
+        let market_id = B256::from_str(MARKET_ID).unwrap();
+        let question = question_id(market_id, 3);
+        assert_eq!(question.0[31], 3);
+        assert_eq!(question_index(market_id, question).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_question_index_rejects_other_event() {
+        let market_id = B256::from_str(MARKET_ID).unwrap();
+        assert!(question_index(market_id, B256::ZERO).is_err());
+        assert!(question_index(market_id, question_id(market_id, 300)).is_err());
+    }
+
+    #[test]
+    fn test_index_set() {
+        assert_eq!(index_set(&[0]), U256::from(1));
+        assert_eq!(index_set(&[0, 2]), U256::from(5));
+        assert_eq!(index_set(&[]), U256::ZERO);
+    }
+
+    #[test]
+    fn test_preview_conversion_two_nos() {
+        let before = holdings(0, &[0, 0, 0], &[10, 10, 0]);
+        let after = preview_conversion(&before, &[0, 1], U256::from(4u64), U256::ZERO);
+        assert_eq!(after, holdings(4, &[0, 0, 4], &[6, 6, 0]));
+    }
+
+    #[test]
+    fn test_preview_conversion_single_no_with_fee() {
+        let before = holdings(0, &[0, 0], &[100, 0]);
+        let after = preview_conversion(&before, &[0], U256::from(100u64), U256::from(100u64));
+        // 1% fee, no collateral for a single No
+        assert_eq!(after, holdings(0, &[0, 99], &[0, 0]));
+    }
+
+    #[test]
+    fn test_holdings_mismatches() {
+        let expected = holdings(4, &[0, 4], &[6, 0]);
+        assert!(holdings_mismatches(&expected, &expected).is_empty());
+
+        let actual = holdings(4, &[0, 3], &[6, 0]);
+        let mismatches = holdings_mismatches(&expected, &actual);
+        assert_eq!(mismatches.len(), 1);
+        assert!(mismatches[0].starts_with("question = 1, yes"));
+    }
+
+    #[test]
+    fn test_convert_calldata() {
+        let market_id = B256::from_str(MARKET_ID).unwrap();
+        let data = convert_calldata(market_id, &[1, 2], U256::from(7u64));
+        let decoded = INegRiskAdapter::convertPositionsCall::abi_decode(&data).unwrap();
+        assert_eq!(decoded.marketId, market_id);
+        assert_eq!(decoded.indexSet, U256::from(6));
+        assert_eq!(decoded.amount, U256::from(7u64));
+    }
+}
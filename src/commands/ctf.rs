@@ -0,0 +1,207 @@
+use anyhow::{Context, Result};
+use alloy::primitives::{B256, U256};
+use polymarket_client_sdk::{
+    POLYGON,
+    contract_config,
+    types::Address,
+};
+use std::str::FromStr;
+
+use crate::cli::CtfCommand;
+use crate::commands::market::resolve_market;
+use crate::commands::search::parse_outcomes;
+use crate::constants::{NEG_RISK_WRAPPED_COLLATERAL_ADDRESS, USDC_E_ADDRESS};
+use crate::ctf::{collection_id, condition_id, outcome_index_set, position_id};
+
+pub async fn execute(command: CtfCommand) -> Result<()> {
+    match command {
+        CtfCommand::Ids { market, oracle, question_id, outcomes, collateral, neg_risk } => {
+            ids(market, oracle, question_id, outcomes, collateral, neg_risk).await
+        }
+    }
+}
+
+async fn ids(
+    market: Option<String>,
+    oracle: Option<String>,
+    question_id: Option<String>,
+    outcomes: u32,
+    collateral: Option<String>,
+    neg_risk: bool,
+) -> Result<()> {
+    let gamma = match &market {
+        Some(market) => Some(resolve_market(market).await?),
+        None => None,
+    };
+    let neg_risk = neg_risk || gamma.as_ref().and_then(|m| m.neg_risk).unwrap_or(false);
+
+    let question_id = question_id
+        .or_else(|| gamma.as_ref().and_then(|m| m.question_id.clone()))
+        .context("Need --question-id (or --market to look it up)")?;
+    let question_id = B256::from_str(&question_id).context("Invalid question ID")?;
+
+    // Neg-risk conditions are prepared by the Neg Risk Adapter, which acts as their oracle.
+    let oracle = match oracle {
+        Some(oracle) => Address::from_str(&oracle).context("Invalid oracle address")?,
+        None if neg_risk => contract_config(POLYGON, true)
+            .and_then(|c| c.neg_risk_adapter)
+            .context("No Neg Risk Adapter configured")?,
+        None => {
+            let resolved_by = gamma.as_ref().and_then(|m| m.resolved_by.clone());
+            let resolved_by = resolved_by.context("Need --oracle (or --market to look it up)")?;
+            Address::from_str(&resolved_by).context("Invalid oracle address from Gamma")?
+        }
+    };
+
+    let collateral = match collateral {
+        Some(collateral) => Address::from_str(&collateral).context("Invalid collateral address")?,
+        None => default_collateral(neg_risk),
+    };
+
+    let ids = compute_ids(oracle, question_id, outcomes, collateral);
+    println!("oracle = {}, question_id = {}, outcomes = {}", oracle, question_id, outcomes);
+    println!("collateral = {}, neg_risk = {}", collateral, neg_risk);
+    println!("Condition ID: {}", ids.condition_id);
+
+    let gamma_outcomes = gamma
+        .as_ref()
+        .and_then(|m| parse_outcomes(m.outcomes.as_deref()?, m.clob_token_ids.as_deref()?))
+        .unwrap_or_default();
+    for (index, position) in ids.positions.iter().enumerate() {
+        let name = gamma_outcomes.get(index).map(|(name, _)| name.as_str()).unwrap_or("-");
+        println!("- Outcome {} ({}):", index, name);
+        println!("  Index Set: {}", position.index_set);
+        println!("  Collection ID: {}", position.collection_id);
+        println!("  Position ID: {}", position.position_id);
+    }
+
+    let Some(gamma) = gamma else { return Ok(()) };
+    let mismatches = cross_check(&ids, gamma.condition_id.as_deref(), &gamma_outcomes);
+    if mismatches.is_empty() {
+        println!("Matches Gamma: condition ID and {} token IDs", gamma_outcomes.len());
+        Ok(())
+    } else {
+        for mismatch in &mismatches {
+            println!("MISMATCH: {}", mismatch);
+        }
+        anyhow::bail!("{} IDs don't match Gamma", mismatches.len())
+    }
+}
+
+/// Positions of neg-risk markets are backed by the adapter's wrapped collateral.
+pub fn default_collateral(neg_risk: bool) -> Address {
+    if neg_risk { NEG_RISK_WRAPPED_COLLATERAL_ADDRESS } else { USDC_E_ADDRESS }
+}
+
+/// IDs of a condition and each of its single-outcome positions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CtfIds {
+    pub condition_id: B256,
+    pub positions: Vec<PositionIds>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PositionIds {
+    pub index_set: U256,
+    pub collection_id: B256,
+    pub position_id: U256,
+}
+
+pub fn compute_ids(oracle: Address, question_id: B256, outcomes: u32, collateral: Address) -> CtfIds {
+    let condition_id = condition_id(oracle, question_id, outcomes);
+    let positions = (0..outcomes)
+        .map(|index| {
+            let index_set = outcome_index_set(index);
+            let collection_id = collection_id(condition_id, index_set);
+            PositionIds {
+                index_set,
+                collection_id,
+                position_id: position_id(collateral, collection_id),
+            }
+        })
+        .collect();
+    CtfIds { condition_id, positions }
+}
+
+/// Compare computed IDs with Gamma's condition ID and `(outcome, token_id)` pairs.
+pub fn cross_check(ids: &CtfIds, gamma_condition_id: Option<&str>, gamma_outcomes: &[(String, String)]) -> Vec<String> {
+    let mut mismatches = Vec::new();
+    match gamma_condition_id.map(B256::from_str) {
+        Some(Ok(expected)) if expected == ids.condition_id => {}
+        Some(Ok(expected)) => mismatches.push(format!("condition ID: computed {}, Gamma has {}", ids.condition_id, expected)),
+        Some(Err(_)) | None => mismatches.push("condition ID: Gamma has no valid condition ID".to_string()),
+    }
+
+    if gamma_outcomes.len() != ids.positions.len() {
+        mismatches.push(format!(
+            "outcomes: computed {}, Gamma has {} token IDs",
+            ids.positions.len(),
+            gamma_outcomes.len()
+        ));
+    }
+    for (index, (position, (outcome, token_id))) in ids.positions.iter().zip(gamma_outcomes).enumerate() {
+        if U256::from_str(token_id).ok() != Some(position.position_id) {
+            mismatches.push(format!(
+                "outcome {} ({}): computed {}, Gamma has {}",
+                index, outcome, position.position_id, token_id
+            ));
+        }
+    }
+    mismatches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_ids() -> CtfIds {
+        compute_ids(Address::repeat_byte(0x11), B256::repeat_byte(0x22), 2, USDC_E_ADDRESS)
+    }
+
+    #[test]
+    fn test_compute_ids_index_sets() {
+        let ids = sample_ids();
+        assert_eq!(ids.positions.len(), 2);
+        assert_eq!(ids.positions[0].index_set, U256::from(1));
+        assert_eq!(ids.positions[1].index_set, U256::from(2));
+        assert_ne!(ids.positions[0].position_id, ids.positions[1].position_id);
+    }
+
+    #[test]
+    fn test_default_collateral() {
+        assert_eq!(default_collateral(false), USDC_E_ADDRESS);
+        assert_eq!(default_collateral(true), NEG_RISK_WRAPPED_COLLATERAL_ADDRESS);
+    }
+
+    #[test]
+    fn test_cross_check_matching() {
+        let ids = sample_ids();
+        let outcomes = vec![
+            ("Yes".to_string(), ids.positions[0].position_id.to_string()),
+            ("No".to_string(), ids.positions[1].position_id.to_string()),
+        ];
+        let condition = ids.condition_id.to_string();
+        assert!(cross_check(&ids, Some(&condition), &outcomes).is_empty());
+    }
+
+    #[test]
+    fn test_cross_check_swapped_tokens() {
+        let ids = sample_ids();
+        let outcomes = vec![
+            ("Yes".to_string(), ids.positions[1].position_id.to_string()),
+            ("No".to_string(), ids.positions[0].position_id.to_string()),
+        ];
+        let condition = ids.condition_id.to_string();
+        let mismatches = cross_check(&ids, Some(&condition), &outcomes);
+        assert_eq!(mismatches.len(), 2);
+        assert!(mismatches[0].starts_with("outcome 0 (Yes)"));
+    }
+
+    #[test]
+    fn test_cross_check_condition_mismatch() {
+        let ids = sample_ids();
+        let mismatches = cross_check(&ids, Some(&B256::ZERO.to_string()), &[]);
+        assert_eq!(mismatches.len(), 2);
+        assert!(mismatches[0].starts_with("condition ID"));
+    }
+}
@@ -0,0 +1,199 @@
+use anyhow::{Context, Result};
+use polymarket_client_sdk::{
+    clob::{Client as ClobClient, Config as ClobConfig},
+    gamma::{
+        Client as GammaClient,
+        types::{
+            request::{EventByIdRequest, EventBySlugRequest},
+            response::{Event, Market},
+        },
+    },
+    types::Decimal,
+};
+
+use crate::commands::market::yes_token_id;
+use crate::commands::orderbook::{Quote, fetch_order_books};
+
+pub async fn execute(event: String) -> Result<()> {
+    let event = fetch_event(&event).await?;
+    let markets = event.markets.unwrap_or_default();
+    anyhow::ensure!(!markets.is_empty(), "Event {} has no markets", event.id);
+
+    println!("Event: {} (ID: {})", event.title.as_deref().unwrap_or_default(), event.id);
+    println!("Neg Risk: {}", event.neg_risk.unwrap_or(false));
+
+    let mut rows: Vec<OutcomeRow> = markets.iter().filter_map(OutcomeRow::from_market).collect();
+    let token_ids: Vec<String> = rows.iter().map(|r| r.token_id.clone()).collect();
+    let client = ClobClient::new("https://clob.polymarket.com", ClobConfig::default())?;
+    let books = fetch_order_books(&client, &token_ids).await?;
+    for row in &mut rows {
+        row.quote = books.get(&row.token_id).map(Quote::from_book).unwrap_or_default();
+    }
+    rows.sort_by_key(|r| std::cmp::Reverse(r.quote.mid()));
+
+    let width = rows.iter().map(|r| r.label.chars().count()).max().unwrap_or(0).max("Outcome".len());
+    println!("{:<width$}  {:>7}  {:>7}  {:>7}", "Outcome", "Bid", "Ask", "Mid", width = width);
+    for row in &rows {
+        let mut flags = Vec::new();
+        if row.closed {
+            flags.push("closed");
+        }
+        match (row.quote.bid, row.quote.ask) {
+            (None, None) => flags.push("no liquidity"),
+            (None, Some(_)) => flags.push("no bids"),
+            (Some(_), None) => flags.push("no asks"),
+            (Some(_), Some(_)) => {}
+        }
+        println!(
+            "{:<width$}  {:>7}  {:>7}  {:>7}{}",
+            row.label,
+            display_price(row.quote.bid),
+            display_price(row.quote.ask),
+            display_price(row.quote.mid()),
+            if flags.is_empty() { String::new() } else { format!("  ({})", flags.join(", ")) },
+            width = width
+        );
+    }
+
+    let quotes: Vec<Quote> = rows.iter().filter(|r| !r.closed).map(|r| r.quote).collect();
+    let summary = ImpliedSummary::from_quotes(&quotes);
+    println!("sum_mid = {}, sum_bid = {}, sum_ask = {}", summary.sum_mid, summary.sum_bid, summary.sum_ask);
+    println!("overround = {}%", (summary.overround() * Decimal::ONE_HUNDRED).round_dp(2));
+    if summary.unpriced > 0 {
+        println!(
+            "WARNING: {} of {} open outcomes have no two-sided book and are left out of the sums",
+            summary.unpriced,
+            quotes.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// Look up a Gamma event by numeric ID or slug.
+pub async fn fetch_event(event: &str) -> Result<Event> {
+    let client = GammaClient::default();
+    if !event.is_empty() && event.chars().all(|c| c.is_ascii_digit()) {
+        let request = EventByIdRequest::builder().id(event).build();
+        client.event_by_id(&request).await.context("Failed to fetch event")
+    } else {
+        let request = EventBySlugRequest::builder().slug(event).build();
+        client.event_by_slug(&request).await.context("Failed to fetch event")
+    }
+}
+
+/// One market of an event, priced by its YES token.
+#[derive(Debug, Clone)]
+pub struct OutcomeRow {
+    pub label: String,
+    pub token_id: String,
+    pub closed: bool,
+    pub quote: Quote,
+}
+
+impl OutcomeRow {
+    /// Markets without outcome token IDs can't be priced and are skipped.
+    pub fn from_market(market: &Market) -> Option<Self> {
+        let token_id = yes_token_id(market)?;
+        let label = market
+            .group_item_title
+            .clone()
+            .filter(|t| !t.is_empty())
+            .or_else(|| market.question.clone())
+            .unwrap_or_else(|| market.id.clone());
+        Some(OutcomeRow {
+            label,
+            token_id,
+            closed: market.closed.unwrap_or(false),
+            quote: Quote::default(),
+        })
+    }
+}
+
+/// Implied probabilities of the outcomes of an event, summed. Outcomes without a two-sided
+/// book count as unpriced and are left out of every sum, so the sums stay comparable.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImpliedSummary {
+    pub sum_mid: Decimal,
+    pub sum_bid: Decimal,
+    pub sum_ask: Decimal,
+    pub unpriced: usize,
+}
+
+impl ImpliedSummary {
+    pub fn from_quotes(quotes: &[Quote]) -> Self {
+        let mut summary = ImpliedSummary::default();
+        for quote in quotes {
+            match (quote.bid, quote.ask) {
+                (Some(bid), Some(ask)) => {
+                    summary.sum_bid += bid;
+                    summary.sum_ask += ask;
+                    summary.sum_mid += (bid + ask) / Decimal::TWO;
+                }
+                _ => summary.unpriced += 1,
+            }
+        }
+        summary
+    }
+
+    /// How far the implied probabilities add up above 100% (negative when below).
+    pub fn overround(&self) -> Decimal {
+        self.sum_mid - Decimal::ONE
+    }
+}
+
+fn display_price(price: Option<Decimal>) -> String {
+    price.map(|p| p.normalize().to_string()).unwrap_or_else(|| "-".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn quote(bid: Option<&str>, ask: Option<&str>) -> Quote {
+        Quote { bid: bid.map(dec), ask: ask.map(dec) }
+    }
+
+    #[test]
+    fn test_implied_summary() {
+        let quotes = [
+            quote(Some("0.50"), Some("0.52")),
+            quote(Some("0.30"), Some("0.32")),
+            quote(Some("0.20"), Some("0.22")),
+            quote(None, Some("0.05")),
+        ];
+        let summary = ImpliedSummary::from_quotes(&quotes);
+        assert_eq!(summary.sum_bid, dec("1.00"));
+        assert_eq!(summary.sum_ask, dec("1.06"));
+        assert_eq!(summary.sum_mid, dec("1.03"));
+        assert_eq!(summary.overround(), dec("0.03"));
+        assert_eq!(summary.unpriced, 1);
+    }
+
+    #[test]
+    fn test_outcome_row_prefers_yes_token() {
+        let market: Market = serde_json::from_value(serde_json::json!({
+            "id": "7",
+            "question": "Will Alice win?",
+            "groupItemTitle": "Alice",
+            "outcomes": "[\"No\",\"Yes\"]",
+            "clobTokenIds": "[\"111\",\"222\"]"
+        }))
+        .unwrap();
+        let row = OutcomeRow::from_market(&market).unwrap();
+        assert_eq!(row.label, "Alice");
+        assert_eq!(row.token_id, "222");
+        assert!(!row.closed);
+    }
+
+    #[test]
+    fn test_outcome_row_without_tokens() {
+        let market: Market = serde_json::from_value(serde_json::json!({ "id": "8" })).unwrap();
+        assert!(OutcomeRow::from_market(&market).is_none());
+    }
+}
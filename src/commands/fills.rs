@@ -0,0 +1,301 @@
+use anyhow::{Context, Result};
+use alloy::primitives::{B256, U256};
+use alloy::providers::{Provider, ProviderBuilder};
+use alloy::rpc::types::{Filter, Log};
+use alloy::sol_types::SolEvent;
+use polymarket_client_sdk::{
+    POLYGON, PRIVATE_KEY_VAR,
+    auth::LocalSigner,
+    clob::types::SignatureType,
+    contract_config,
+    data::{Client as DataClient, types::request::TradesRequest},
+    types::{Address, Decimal},
+};
+use std::env;
+use std::str::FromStr;
+
+use crate::commands::status::format_balance;
+use crate::contracts::ICtfExchange::OrderFilled;
+use crate::rpc;
+use crate::wallet::funder_address;
+
+/// Blocks scanned when no start block is given: about a day of Polygon blocks.
+const DEFAULT_LOOKBACK_BLOCKS: u64 = 43_200;
+/// Trades fetched per address from the data API.
+const API_TRADES_LIMIT: i32 = 100;
+
+pub async fn execute(
+    users: Vec<String>,
+    onchain: bool,
+    from_block: Option<u64>,
+    to_block: Option<u64>,
+    chunk_size: u64,
+    rpc_urls: &[String],
+) -> Result<()> {
+    let addresses = resolve_addresses(&users)?;
+    println!(
+        "Fills for {}:",
+        addresses.iter().map(|a| a.to_string()).collect::<Vec<_>>().join(", ")
+    );
+
+    if !onchain {
+        return print_api_fills(&addresses).await;
+    }
+
+    let provider = ProviderBuilder::new().connect_client(rpc::connect(rpc_urls).await?);
+    let latest = provider.get_block_number().await.context("Failed to get latest block")?;
+    let to_block = to_block.unwrap_or(latest).min(latest);
+    let from_block = from_block.unwrap_or_else(|| to_block.saturating_sub(DEFAULT_LOOKBACK_BLOCKS));
+    anyhow::ensure!(from_block <= to_block, "--from-block is after --to-block");
+    println!("blocks = {}..={}, chunk_size = {}", from_block, to_block, chunk_size);
+
+    let exchanges = exchange_addresses()?;
+    let exchange_list: Vec<Address> = exchanges.iter().map(|(_, address)| *address).collect();
+    let logs = scan_order_filled(&provider, &exchange_list, &addresses, from_block, to_block, chunk_size).await?;
+
+    let mut fills = Vec::new();
+    for log in &logs {
+        let event = log.log_decode::<OrderFilled>().context("Failed to decode OrderFilled log")?;
+        let Some(terms) = fill_terms(&event.inner.data, &addresses, log.address()) else {
+            continue;
+        };
+        let exchange = exchanges
+            .iter()
+            .find(|(_, address)| *address == log.address())
+            .map(|(label, _)| *label)
+            .unwrap_or("Unknown");
+        fills.push(OnchainFill {
+            block: log.block_number.unwrap_or_default(),
+            log_index: log.log_index.unwrap_or_default(),
+            tx_hash: log.transaction_hash.unwrap_or_default(),
+            exchange,
+            terms,
+        });
+    }
+    fills.sort_by_key(|f| (f.block, f.log_index));
+
+    for fill in &fills {
+        let terms = &fill.terms;
+        println!(
+            "- block = {}, exchange = {}, role = {}, side = {}, price = {}, size = {}, fee = {} {}",
+            fill.block,
+            fill.exchange,
+            terms.role,
+            if terms.buy { "BUY" } else { "SELL" },
+            terms.price,
+            terms.size,
+            terms.fee,
+            if terms.fee_in_shares { "shares" } else { "USDC" }
+        );
+        println!("  Token ID: {}", terms.token_id);
+        println!("  Tx: {}", fill.tx_hash);
+    }
+    println!("fills = {}", fills.len());
+
+    Ok(())
+}
+
+/// Addresses to look for: explicit `--user` values, else `USER_ADDRESS`, else the EOA and
+/// the proxy and Safe wallets derived from the private key.
+fn resolve_addresses(users: &[String]) -> Result<Vec<Address>> {
+    if !users.is_empty() {
+        return users
+            .iter()
+            .map(|u| Address::from_str(u).with_context(|| format!("Invalid address: {}", u)))
+            .collect();
+    }
+    if let Ok(u) = env::var("USER_ADDRESS") {
+        return Ok(vec![Address::from_str(&u).context("Invalid address format in USER_ADDRESS")?]);
+    }
+    let private_key = env::var(PRIVATE_KEY_VAR).context("PRIVATE_KEY or USER_ADDRESS env var not set")?;
+    let owner = LocalSigner::from_str(&private_key).context("Invalid private key")?.address();
+    Ok(vec![
+        owner,
+        funder_address(owner, SignatureType::Proxy)?,
+        funder_address(owner, SignatureType::GnosisSafe)?,
+    ])
+}
+
+/// The CTF Exchange and the Neg Risk CTF Exchange, which both emit `OrderFilled`.
+fn exchange_addresses() -> Result<Vec<(&'static str, Address)>> {
+    let config = contract_config(POLYGON, false).context("Failed to get contract config")?;
+    let neg_risk_config = contract_config(POLYGON, true).context("Failed to get neg risk contract config")?;
+    Ok(vec![
+        ("CTF Exchange", config.exchange),
+        ("Neg Risk CTF Exchange", neg_risk_config.exchange),
+    ])
+}
+
+/// Fetch `OrderFilled` logs of orders placed by any of the addresses.
+///
+/// Every fill of an order emits an event with the order's owner as `maker`, whether it
+/// rested on the book or crossed it, so only the maker topic is queried. Events with
+/// us as `taker` describe the counterparty's order and would count our fills twice.
+async fn scan_order_filled<P: Provider>(
+    provider: &P,
+    exchanges: &[Address],
+    addresses: &[Address],
+    from_block: u64,
+    to_block: u64,
+    chunk_size: u64,
+) -> Result<Vec<Log>> {
+    let topics: Vec<B256> = addresses.iter().map(|a| a.into_word()).collect();
+    let filter = Filter::new()
+        .address(exchanges.to_vec())
+        .event_signature(OrderFilled::SIGNATURE_HASH)
+        .topic2(topics);
+    rpc::get_logs_chunked(provider, &filter, from_block, to_block, chunk_size).await
+}
+
+struct OnchainFill {
+    block: u64,
+    log_index: u64,
+    tx_hash: B256,
+    exchange: &'static str,
+    terms: FillTerms,
+}
+
+/// A fill seen from our side of the trade.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FillTerms {
+    /// "maker" when our resting order was filled, "taker" when our order crossed the book.
+    pub role: &'static str,
+    pub buy: bool,
+    pub token_id: U256,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub fee: Decimal,
+    /// The fee is taken from what the order receives: shares on a buy, USDC on a sell.
+    pub fee_in_shares: bool,
+}
+
+/// Decode price, size and side from an `OrderFilled` event, or `None` if the event is
+/// not about one of our orders.
+///
+/// Asset ID 0 is collateral. The event describes the order of `maker`: a resting order
+/// filled by a taker, or, with the exchange as `taker`, a taker order matched against
+/// the book. A taker order's match also emits one event per resting order it filled,
+/// with our address as `taker`; those carry the counterparty's side and, when the match
+/// mints or merges, the complementary token, so they are skipped.
+pub fn fill_terms(event: &OrderFilled, ours: &[Address], exchange: Address) -> Option<FillTerms> {
+    if !ours.contains(&event.maker) {
+        return None;
+    }
+    let maker_buys = event.makerAssetId.is_zero();
+    let (token_id, usdc, shares) = if maker_buys {
+        (event.takerAssetId, event.makerAmountFilled, event.takerAmountFilled)
+    } else {
+        (event.makerAssetId, event.takerAmountFilled, event.makerAmountFilled)
+    };
+    let usdc = format_balance(usdc);
+    let size = format_balance(shares);
+    let price = if size.is_zero() { Decimal::ZERO } else { (usdc / size).round_dp(6).normalize() };
+
+    Some(FillTerms {
+        role: if event.taker == exchange { "taker" } else { "maker" },
+        buy: maker_buys,
+        token_id,
+        price,
+        size,
+        fee: format_balance(event.fee),
+        fee_in_shares: maker_buys,
+    })
+}
+
+/// Fills as reported by the data API, for comparison with the on-chain view.
+async fn print_api_fills(addresses: &[Address]) -> Result<()> {
+    let client = DataClient::default();
+    let mut count = 0;
+    for address in addresses {
+        let request = TradesRequest::builder()
+            .user(*address)
+            .taker_only(false)
+            .limit(API_TRADES_LIMIT)?
+            .build();
+        let trades = client.trades(&request).await.context("Failed to fetch trades")?;
+        for trade in trades {
+            count += 1;
+            println!(
+                "- time = {}, side = {:?}, price = {}, size = {}, market = {} ({})",
+                trade.timestamp, trade.side, trade.price, trade.size, trade.title, trade.outcome
+            );
+            println!("  Token ID: {}", trade.asset);
+            println!("  Tx: {}", trade.transaction_hash);
+        }
+    }
+    println!("fills = {}", count);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OURS: Address = Address::repeat_byte(0xaa);
+    const OTHER: Address = Address::repeat_byte(0xbb);
+    const EXCHANGE: Address = Address::repeat_byte(0xee);
+
+    fn event(maker: Address, taker: Address, maker_asset: u64, taker_asset: u64, maker_amount: u64, taker_amount: u64) -> OrderFilled {
+        OrderFilled {
+            orderHash: B256::ZERO,
+            maker,
+            taker,
+            makerAssetId: U256::from(maker_asset),
+            takerAssetId: U256::from(taker_asset),
+            makerAmountFilled: U256::from(maker_amount),
+            takerAmountFilled: U256::from(taker_amount),
+            fee: U256::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_fill_terms_maker_buy() {
+        // We pay 6 USDC for 10 shares of token 42
+        let terms = fill_terms(&event(OURS, OTHER, 0, 42, 6_000_000, 10_000_000), &[OURS], EXCHANGE).unwrap();
+        assert_eq!(terms.role, "maker");
+        assert!(terms.buy);
+        assert_eq!(terms.token_id, U256::from(42));
+        assert_eq!(terms.price, Decimal::from_str("0.6").unwrap());
+        assert_eq!(terms.size, Decimal::from(10));
+        assert!(terms.fee_in_shares);
+    }
+
+    #[test]
+    fn test_fill_terms_taker_order_sell() {
+        // Our taker order sells 5 shares for 2 USDC; its event has the exchange as taker
+        let terms = fill_terms(&event(OURS, EXCHANGE, 42, 0, 5_000_000, 2_000_000), &[OURS], EXCHANGE).unwrap();
+        assert_eq!(terms.role, "taker");
+        assert!(!terms.buy);
+        assert_eq!(terms.price, Decimal::from_str("0.4").unwrap());
+        assert!(!terms.fee_in_shares);
+    }
+
+    #[test]
+    fn test_fill_terms_skips_counterparty_events() {
+        // The maker sells to us; the event is about their order, not ours
+        assert!(fill_terms(&event(OTHER, OURS, 42, 0, 5_000_000, 2_000_000), &[OURS], EXCHANGE).is_none());
+    }
+
+    #[test]
+    fn test_fill_terms_taker_match_counted_once() {
+        // One matchOrders tx: our order buys 30 YES (token 1) for 18 USDC against two resting
+        // orders. One buys 10 NO (token 2) at 0.40, minting a set; the other sells 20 YES at 0.60.
+        let events = [
+            event(OTHER, OURS, 0, 2, 4_000_000, 10_000_000),
+            event(OTHER, OURS, 1, 0, 20_000_000, 12_000_000),
+            event(OURS, EXCHANGE, 0, 1, 18_000_000, 30_000_000),
+        ];
+        let fills: Vec<FillTerms> = events.iter().filter_map(|e| fill_terms(e, &[OURS], EXCHANGE)).collect();
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].role, "taker");
+        assert!(fills[0].buy);
+        assert_eq!(fills[0].token_id, U256::from(1));
+        assert_eq!(fills[0].price, Decimal::from_str("0.6").unwrap());
+        assert_eq!(fills[0].size, Decimal::from(30));
+    }
+
+    #[test]
+    fn test_fill_terms_not_ours() {
+        assert!(fill_terms(&event(OTHER, OTHER, 0, 42, 1, 1), &[OURS], EXCHANGE).is_none());
+    }
+}
@@ -0,0 +1,460 @@
+use anyhow::{Context, Result};
+use polymarket_client_sdk::{
+    clob::{
+        Client as ClobClient, Config as ClobConfig,
+        types::{TimeRange, request::PriceHistoryRequest},
+    },
+    types::{DateTime, Decimal, Utc},
+};
+use rust_decimal::prelude::ToPrimitive;
+
+use crate::chart;
+use crate::commands::market::condition_id_for_token;
+use crate::commands::search::{parse_date_bound, parse_duration_secs};
+use crate::commands::trade::{TradesQuery, fetch_market_trades};
+
+/// History fetched when no `--from` is given.
+const DEFAULT_LOOKBACK_SECS: i64 = 7 * 86_400;
+const SECS_PER_YEAR: f64 = 365.0 * 86_400.0;
+/// Rows of the `--chart` plot.
+const CHART_HEIGHT: usize = 15;
+
+pub async fn execute(
+    token_id: String,
+    interval: String,
+    from: Option<String>,
+    to: Option<String>,
+    ohlc: bool,
+    format: String,
+    chart: bool,
+) -> Result<()> {
+    let format = parse_format(&format)?;
+    anyhow::ensure!(!chart || format == Format::Table, "--chart only works with --format table");
+    let interval_secs = parse_duration_secs(&interval)
+        .filter(|secs| *secs >= 60)
+        .with_context(|| format!("Invalid interval: {} (use e.g. 1m, 1h or 1d)", interval))?;
+    let now = Utc::now();
+    let to = to.as_deref().map(|v| parse_time_bound(v, now)).transpose()?.unwrap_or(now);
+    let from = match from.as_deref() {
+        Some(from) => parse_time_bound(from, now)?,
+        None => DateTime::from_timestamp(to.timestamp() - DEFAULT_LOOKBACK_SECS, 0).context("Date out of range")?,
+    };
+    anyhow::ensure!(from < to, "--from must be before --to");
+
+    let candles = if ohlc {
+        let condition_id = condition_id_for_token(&token_id).await?;
+        let query = TradesQuery::market_tape(from.timestamp(), to.timestamp(), [token_id.clone()].into());
+        let fetched = fetch_market_trades(&condition_id, &query).await?;
+        if let Some(warning) = fetched.truncation_warning() {
+            eprintln!("{}; candles and stats only cover the trades after that.", warning);
+        }
+        let trades: Vec<(i64, Decimal, Decimal)> =
+            fetched.trades.iter().map(|t| (t.timestamp, t.price, t.size)).collect();
+        candles_from_trades(&trades, interval_secs)
+    } else {
+        fetch_price_series(&token_id, from.timestamp(), to.timestamp(), (interval_secs / 60) as u32)
+            .await?
+            .into_iter()
+            .map(|(t, p)| Candle::point(t, p))
+            .collect()
+    };
+
+    let closes: Vec<(i64, Decimal)> = candles.iter().map(|c| (c.t, c.close)).collect();
+    let stats = Stats::from_closes(&closes);
+    let summary = match &stats {
+        Some(stats) => stats.to_string(),
+        None => "points = 0".to_string(),
+    };
+
+    match format {
+        Format::Table => {
+            println!(
+                "History for {} ({} to {}, every {}):",
+                token_id,
+                format_time(from.timestamp()),
+                format_time(to.timestamp()),
+                interval
+            );
+            if chart {
+                for line in render_chart(&candles, ohlc, chart::terminal_width(), CHART_HEIGHT) {
+                    println!("{}", line);
+                }
+            } else {
+                print!("{}", render_table(&candles, ohlc));
+            }
+            println!("{}", summary);
+        }
+        Format::Csv => {
+            print!("{}", render_csv(&candles, ohlc));
+            eprintln!("{}", summary);
+        }
+        Format::Json => {
+            println!("{}", serde_json::to_string_pretty(&render_json(&candles, ohlc, stats.as_ref()))?);
+        }
+    }
+
+    Ok(())
+}
+
+/// The CLOB price series of a token between two timestamps, oldest first, one point
+/// every `fidelity_minutes`.
+pub async fn fetch_price_series(
+    token_id: &str,
+    start: i64,
+    end: i64,
+    fidelity_minutes: u32,
+) -> Result<Vec<(i64, Decimal)>> {
+    let client = ClobClient::new("https://clob.polymarket.com", ClobConfig::default())?;
+    let request = PriceHistoryRequest::builder()
+        .market(token_id.to_string())
+        .time_range(TimeRange::from_range(start, end))
+        .fidelity(fidelity_minutes)
+        .build();
+    let response = client.price_history(&request).await.context("Failed to fetch price history")?;
+    let mut points: Vec<(i64, Decimal)> = response.history.into_iter().map(|p| (p.t, p.p)).collect();
+    points.sort_by_key(|(t, _)| *t);
+    Ok(points)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Csv,
+    Json,
+}
+
+pub fn parse_format(value: &str) -> Result<Format> {
+    match value.to_lowercase().as_str() {
+        "table" => Ok(Format::Table),
+        "csv" => Ok(Format::Csv),
+        "json" => Ok(Format::Json),
+        _ => anyhow::bail!("Invalid format: must be 'table', 'csv' or 'json'"),
+    }
+}
+
+/// Like `parse_date_bound`, but a bare duration such as `7d` means that long ago.
+pub fn parse_time_bound(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    match parse_duration_secs(value.trim()) {
+        Some(secs) => DateTime::from_timestamp(now.timestamp() - secs, 0).context("Date out of range"),
+        None => parse_date_bound(value, now),
+    }
+}
+
+/// One bar of the series. Points of the CLOB price series are bars with all four prices
+/// equal and no volume.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candle {
+    pub t: i64,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    pub volume: Decimal,
+}
+
+impl Candle {
+    pub fn point(t: i64, price: Decimal) -> Self {
+        Candle { t, open: price, high: price, low: price, close: price, volume: Decimal::ZERO }
+    }
+}
+
+/// Bucket `(timestamp, price, size)` trades into bars of `interval_secs`, oldest first.
+/// Intervals without trades get no bar.
+pub fn candles_from_trades(trades: &[(i64, Decimal, Decimal)], interval_secs: i64) -> Vec<Candle> {
+    let mut trades = trades.to_vec();
+    trades.sort_by_key(|(t, _, _)| *t);
+    let mut candles: Vec<Candle> = Vec::new();
+    for (t, price, size) in trades {
+        let bucket = t - t.rem_euclid(interval_secs);
+        match candles.last_mut() {
+            Some(candle) if candle.t == bucket => {
+                candle.high = candle.high.max(price);
+                candle.low = candle.low.min(price);
+                candle.close = price;
+                candle.volume += size;
+            }
+            _ => candles.push(Candle { t: bucket, open: price, high: price, low: price, close: price, volume: size }),
+        }
+    }
+    candles
+}
+
+/// Summary of a series of closing prices.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub points: usize,
+    pub first: Decimal,
+    pub last: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    /// Standard deviation of log returns between consecutive points.
+    pub volatility: Option<f64>,
+    /// `volatility` scaled to a year of the average spacing between points, so gaps in a
+    /// series (intervals without trades) don't count as extra periods.
+    pub annualized_volatility: Option<f64>,
+}
+
+impl Stats {
+    /// Stats of `(timestamp, close)` points, oldest first.
+    pub fn from_closes(closes: &[(i64, Decimal)]) -> Option<Self> {
+        let (first_t, first) = *closes.first()?;
+        let (last_t, last) = *closes.last()?;
+        let returns: Vec<f64> = closes
+            .windows(2)
+            .filter_map(|w| {
+                let (a, b) = (w[0].1.to_f64()?, w[1].1.to_f64()?);
+                (a > 0.0 && b > 0.0).then(|| (b / a).ln())
+            })
+            .collect();
+        let spacing_secs = (last_t - first_t) as f64 / (closes.len() - 1).max(1) as f64;
+        let volatility = (returns.len() >= 2).then(|| {
+            let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+            let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+            variance.sqrt()
+        });
+        Some(Stats {
+            points: closes.len(),
+            first,
+            last,
+            high: closes.iter().map(|(_, c)| *c).max()?,
+            low: closes.iter().map(|(_, c)| *c).min()?,
+            volatility,
+            annualized_volatility: volatility
+                .filter(|_| spacing_secs > 0.0)
+                .map(|v| v * (SECS_PER_YEAR / spacing_secs).sqrt()),
+        })
+    }
+
+    pub fn change(&self) -> Decimal {
+        self.last - self.first
+    }
+
+    pub fn change_pct(&self) -> Option<Decimal> {
+        (!self.first.is_zero()).then(|| self.change() / self.first * Decimal::ONE_HUNDRED)
+    }
+}
+
+impl std::fmt::Display for Stats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let vol = |v: Option<f64>| v.map(|v| format!("{:.4}", v)).unwrap_or_else(|| "-".to_string());
+        write!(
+            f,
+            "points = {}, first = {}, last = {}, change = {} ({}%), high = {}, low = {}, volatility = {}, annualized_volatility = {}",
+            self.points,
+            self.first.normalize(),
+            self.last.normalize(),
+            self.change().normalize(),
+            self.change_pct().map(|p| p.round_dp(2).to_string()).unwrap_or_else(|| "-".to_string()),
+            self.high.normalize(),
+            self.low.normalize(),
+            vol(self.volatility),
+            vol(self.annualized_volatility)
+        )
+    }
+}
+
+pub fn format_time(t: i64) -> String {
+    DateTime::from_timestamp(t, 0)
+        .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| t.to_string())
+}
+
+pub fn render_table(candles: &[Candle], ohlc: bool) -> String {
+    let mut out = String::new();
+    if ohlc {
+        out.push_str(&format!(
+            "{:<16}  {:>6}  {:>6}  {:>6}  {:>6}  {:>12}\n",
+            "Time", "Open", "High", "Low", "Close", "Volume"
+        ));
+        for c in candles {
+            out.push_str(&format!(
+                "{:<16}  {:>6}  {:>6}  {:>6}  {:>6}  {:>12}\n",
+                format_time(c.t),
+                c.open.normalize(),
+                c.high.normalize(),
+                c.low.normalize(),
+                c.close.normalize(),
+                c.volume.normalize()
+            ));
+        }
+    } else {
+        out.push_str(&format!("{:<16}  {:>6}\n", "Time", "Price"));
+        for c in candles {
+            out.push_str(&format!("{:<16}  {:>6}\n", format_time(c.t), c.close.normalize()));
+        }
+    }
+    out
+}
+
+/// A line chart of the closes, or a candlestick chart with `ohlc`, `width` columns wide,
+/// with the first and last bar's time underneath.
+pub fn render_chart(candles: &[Candle], ohlc: bool, width: usize, height: usize) -> Vec<String> {
+    let num = |d: Decimal| d.to_f64().unwrap_or_default();
+    let mut lines = if ohlc {
+        let bars: Vec<chart::Ohlc> =
+            candles.iter().map(|c| (num(c.open), num(c.high), num(c.low), num(c.close))).collect();
+        chart::candle_chart(&bars, width, height)
+    } else {
+        let closes: Vec<f64> = candles.iter().map(|c| num(c.close)).collect();
+        chart::line_chart(&closes, width, height)
+    };
+    if let (Some(first), Some(last), Some(top)) = (candles.first(), candles.last(), lines.first()) {
+        let (start, end) = (format_time(first.t), format_time(last.t));
+        let indent = top.chars().position(|c| c == '┤').map_or(0, |p| p + 1);
+        let gap = top.chars().count().saturating_sub(indent + start.len() + end.len()).max(2);
+        lines.push(format!("{}{}{}{}", " ".repeat(indent), start, " ".repeat(gap), end));
+    }
+    lines
+}
+
+pub fn render_csv(candles: &[Candle], ohlc: bool) -> String {
+    let mut out = String::new();
+    if ohlc {
+        out.push_str("timestamp,time,open,high,low,close,volume\n");
+        for c in candles {
+            out.push_str(&format!(
+                "{},{},{},{},{},{},{}\n",
+                c.t,
+                format_time(c.t),
+                c.open.normalize(),
+                c.high.normalize(),
+                c.low.normalize(),
+                c.close.normalize(),
+                c.volume.normalize()
+            ));
+        }
+    } else {
+        out.push_str("timestamp,time,price\n");
+        for c in candles {
+            out.push_str(&format!("{},{},{}\n", c.t, format_time(c.t), c.close.normalize()));
+        }
+    }
+    out
+}
+
+pub fn render_json(candles: &[Candle], ohlc: bool, stats: Option<&Stats>) -> serde_json::Value {
+    let num = |d: Decimal| d.to_f64().unwrap_or_default();
+    let series: Vec<serde_json::Value> = candles
+        .iter()
+        .map(|c| {
+            if ohlc {
+                serde_json::json!({
+                    "t": c.t,
+                    "open": num(c.open),
+                    "high": num(c.high),
+                    "low": num(c.low),
+                    "close": num(c.close),
+                    "volume": num(c.volume),
+                })
+            } else {
+                serde_json::json!({ "t": c.t, "p": num(c.close) })
+            }
+        })
+        .collect();
+    let stats = stats.map(|s| {
+        serde_json::json!({
+            "points": s.points,
+            "first": num(s.first),
+            "last": num(s.last),
+            "change": num(s.change()),
+            "change_pct": s.change_pct().map(num),
+            "high": num(s.high),
+            "low": num(s.low),
+            "volatility": s.volatility,
+            "annualized_volatility": s.annualized_volatility,
+        })
+    });
+    serde_json::json!({ "history": series, "stats": stats })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn test_parse_time_bound_duration_is_ago() {
+        let now = DateTime::parse_from_rfc3339("2024-06-08T00:00:00Z").unwrap().with_timezone(&Utc);
+        assert_eq!(parse_time_bound("7d", now).unwrap().to_rfc3339(), "2024-06-01T00:00:00+00:00");
+        assert_eq!(parse_time_bound("2024-05-01", now).unwrap().to_rfc3339(), "2024-05-01T00:00:00+00:00");
+    }
+
+    #[test]
+    fn test_candles_from_trades() {
+        let trades = [
+            (3_700, dec("0.52"), dec("10")),
+            (3_600, dec("0.50"), dec("5")),
+            (3_650, dec("0.55"), dec("1")),
+            (7_300, dec("0.48"), dec("20")),
+        ];
+        let candles = candles_from_trades(&trades, 3_600);
+        assert_eq!(candles.len(), 2);
+        assert_eq!(
+            candles[0],
+            Candle { t: 3_600, open: dec("0.50"), high: dec("0.55"), low: dec("0.50"), close: dec("0.52"), volume: dec("16") }
+        );
+        assert_eq!(candles[1].t, 7_200);
+        assert_eq!(candles[1].volume, dec("20"));
+    }
+
+    #[test]
+    fn test_stats() {
+        let closes = [(0, dec("0.40")), (86_400, dec("0.50")), (172_800, dec("0.45")), (259_200, dec("0.60"))];
+        let stats = Stats::from_closes(&closes).unwrap();
+        assert_eq!(stats.change(), dec("0.20"));
+        assert_eq!(stats.change_pct(), Some(dec("50")));
+        assert_eq!(stats.high, dec("0.60"));
+        assert_eq!(stats.low, dec("0.40"));
+        let vol = stats.volatility.unwrap();
+        assert!((vol - 0.2108).abs() < 1e-3, "{}", vol);
+        assert!((stats.annualized_volatility.unwrap() - vol * 365f64.sqrt()).abs() < 1e-9);
+        assert!(Stats::from_closes(&[]).is_none());
+        assert_eq!(Stats::from_closes(&[(0, dec("0.5"))]).unwrap().volatility, None);
+    }
+
+    #[test]
+    fn test_stats_annualize_by_actual_spacing() {
+        // Daily bars with two empty days: the three returns span six days, two days apart on average
+        let closes = [(0, dec("0.40")), (86_400, dec("0.50")), (345_600, dec("0.45")), (518_400, dec("0.60"))];
+        let stats = Stats::from_closes(&closes).unwrap();
+        let vol = stats.volatility.unwrap();
+        assert!((stats.annualized_volatility.unwrap() - vol * (365.0f64 / 2.0).sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_render_chart() {
+        let candles: Vec<Candle> =
+            ["0.40", "0.45", "0.50"].iter().enumerate().map(|(i, p)| Candle::point(i as i64 * 3600, dec(p))).collect();
+        let lines = render_chart(&candles, false, 60, 4);
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].starts_with("0.500 ┤"));
+        assert!(lines[3].starts_with("0.400 ┤"));
+        assert_eq!(lines[4].chars().count(), 60);
+        assert!(lines[4].starts_with("       1970-01-01 00:00"));
+        assert!(lines[4].ends_with("1970-01-01 02:00"));
+        assert!(render_chart(&[], true, 60, 4).is_empty());
+    }
+
+    #[test]
+    fn test_render_csv() {
+        let candles = [Candle::point(0, dec("0.50")), Candle::point(3_600, dec("0.55"))];
+        assert_eq!(
+            render_csv(&candles, false),
+            "timestamp,time,price\n0,1970-01-01 00:00,0.5\n3600,1970-01-01 01:00,0.55\n"
+        );
+        assert!(render_csv(&candles, true).starts_with("timestamp,time,open,high,low,close,volume\n0,"));
+    }
+
+    #[test]
+    fn test_render_json() {
+        let candles = [Candle::point(60, dec("0.25"))];
+        let json = render_json(&candles, false, None);
+        assert_eq!(json["history"][0]["t"], 60);
+        assert_eq!(json["history"][0]["p"], 0.25);
+        assert!(json["stats"].is_null());
+    }
+}
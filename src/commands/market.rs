@@ -0,0 +1,374 @@
+use anyhow::{Context, Result};
+use polymarket_client_sdk::{
+    clob::{Client as ClobClient, Config as ClobConfig, types::request::MidpointRequest},
+    gamma::{
+        Client as GammaClient,
+        types::{
+            request::{MarketByIdRequest, MarketBySlugRequest, MarketsRequest},
+            response::Market,
+        },
+    },
+    types::Decimal,
+};
+use std::collections::HashMap;
+
+use crate::cli::TokenArgs;
+use crate::commands::search::parse_outcomes;
+
+/// Token IDs are 256-bit numbers; Gamma market IDs are short integers.
+const MAX_MARKET_ID_DIGITS: usize = 20;
+
+pub async fn execute(market: String) -> Result<()> {
+    let market = resolve_market(&market).await?;
+
+    println!("Market: {}", market.question.as_deref().unwrap_or_default());
+    println!("ID: {}", market.id);
+    println!("Slug: {}", market.slug.as_deref().unwrap_or("-"));
+    println!("Condition ID: {}", market.condition_id.as_deref().unwrap_or("-"));
+    println!("Status: {}", market_status(&market));
+    println!("End Date: {}", market.end_date.map(|d| d.to_rfc3339()).unwrap_or_else(|| "-".to_string()));
+    println!("Neg Risk: {}", market.neg_risk.unwrap_or(false));
+    println!("Tick Size: {}", display_decimal(market.order_price_min_tick_size));
+    println!("Min Order Size: {}", display_decimal(market.order_min_size));
+    println!("Volume: ${}", display_decimal(market.volume_num));
+    println!("24h Volume: ${}", display_decimal(market.volume_24hr));
+    println!("Liquidity: ${}", display_decimal(market.liquidity_num));
+    println!("Resolution Source: {}", market.resolution_source.as_deref().filter(|s| !s.is_empty()).unwrap_or("-"));
+
+    let outcomes = parse_outcomes(
+        market.outcomes.as_deref().unwrap_or("[]"),
+        market.clob_token_ids.as_deref().unwrap_or("[]"),
+    )
+    .unwrap_or_default();
+    let midpoints = fetch_midpoints(&outcomes).await;
+    println!("Outcomes:");
+    for (outcome, token_id) in &outcomes {
+        let mid = midpoints.get(token_id).map(|m| m.normalize().to_string()).unwrap_or_else(|| "-".to_string());
+        println!("  - {}: {} (midpoint: {})", outcome, token_id, mid);
+    }
+
+    println!("Description:");
+    for line in market.description.as_deref().unwrap_or_default().lines() {
+        println!("  {}", line);
+    }
+
+    Ok(())
+}
+
+/// The ways a market can be referred to on the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarketRef {
+    Id(String),
+    Slug(String),
+    ConditionId(String),
+    TokenId(String),
+}
+
+/// Tell apart a Gamma market ID, a condition ID (`0x` + 64 hex digits), an outcome token ID
+/// (a long decimal number) and a slug.
+pub fn parse_market_ref(input: &str) -> MarketRef {
+    let input = input.trim();
+    let hex = input.strip_prefix("0x").unwrap_or_default();
+    if hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        MarketRef::ConditionId(input.to_string())
+    } else if !input.is_empty() && input.chars().all(|c| c.is_ascii_digit()) {
+        if input.len() > MAX_MARKET_ID_DIGITS {
+            MarketRef::TokenId(input.to_string())
+        } else {
+            MarketRef::Id(input.to_string())
+        }
+    } else {
+        MarketRef::Slug(input.to_string())
+    }
+}
+
+/// Look up a Gamma market by ID, slug, condition ID or outcome token ID.
+pub async fn resolve_market(input: &str) -> Result<Market> {
+    let client = GammaClient::default();
+    match parse_market_ref(input) {
+        MarketRef::Id(id) => {
+            let request = MarketByIdRequest::builder().id(id).build();
+            client.market_by_id(&request).await.context("Failed to fetch market")
+        }
+        MarketRef::Slug(slug) => {
+            let request = MarketBySlugRequest::builder().slug(slug).build();
+            client.market_by_slug(&request).await.context("Failed to fetch market")
+        }
+        MarketRef::ConditionId(condition_id) => {
+            let request = MarketsRequest::builder().condition_ids(vec![condition_id.clone()]).build();
+            let markets = client.markets(&request).await.context("Failed to fetch market")?;
+            markets.into_iter().next().with_context(|| format!("No market with condition ID {}", condition_id))
+        }
+        MarketRef::TokenId(token_id) => {
+            let request = MarketsRequest::builder().clob_token_ids(vec![token_id.clone()]).build();
+            let markets = client.markets(&request).await.context("Failed to fetch market")?;
+            markets.into_iter().next().with_context(|| format!("No market with token ID {}", token_id))
+        }
+    }
+}
+
+/// Turn `--token-id`, or `--market` with `--outcome` (or `market:Outcome`), into a token ID.
+pub async fn resolve_token(args: TokenArgs) -> Result<String> {
+    resolve_token_with(args, false).await
+}
+
+/// Like `resolve_token`, but the outcome must be named in full, for commands that trade:
+/// a prefix like `n` or a typo that happens to start another outcome's name is an error.
+pub async fn resolve_token_exact(args: TokenArgs) -> Result<String> {
+    resolve_token_with(args, true).await
+}
+
+async fn resolve_token_with(args: TokenArgs, exact: bool) -> Result<String> {
+    if let Some(token_id) = args.token_id {
+        return Ok(token_id);
+    }
+    let market = args.market.context("Need --token-id or --market")?;
+    let (market, outcome) = match args.outcome {
+        Some(outcome) => (market, Some(outcome)),
+        None => split_market_outcome(&market),
+    };
+    if let (MarketRef::TokenId(token_id), None) = (parse_market_ref(&market), &outcome) {
+        return Ok(token_id);
+    }
+
+    let gamma = resolve_market(&market).await?;
+    let outcomes = parse_outcomes(
+        gamma.outcomes.as_deref().unwrap_or("[]"),
+        gamma.clob_token_ids.as_deref().unwrap_or("[]"),
+    )
+    .with_context(|| format!("Market {} has no outcome token IDs", market))?;
+    let select = if exact { select_outcome_exact } else { select_outcome };
+    let (name, token_id) = select(&outcomes, outcome.as_deref())
+        .with_context(|| format!("In market: {}", gamma.question.as_deref().unwrap_or(&market)))?;
+    eprintln!("market = {}, outcome = {}, token_id = {}", gamma.slug.as_deref().unwrap_or(&market), name, token_id);
+    Ok(token_id)
+}
+
+/// A market and the outcomes picked from it, as `(outcome, token_id)` pairs.
+#[derive(Debug, Clone)]
+pub struct MarketOutcomes {
+    pub market: Market,
+    pub outcomes: Vec<(String, String)>,
+}
+
+/// Like `resolve_token`, but `--market` without an outcome picks every outcome of the
+/// market, and a bare `--token-id` is looked up to find its market and outcome name.
+pub async fn resolve_outcomes(args: TokenArgs) -> Result<MarketOutcomes> {
+    let (input, outcome) = match (args.token_id, args.market) {
+        (Some(token_id), _) => (token_id, None),
+        (None, Some(market)) => match args.outcome {
+            Some(outcome) => (market, Some(outcome)),
+            None => split_market_outcome(&market),
+        },
+        (None, None) => anyhow::bail!("Need --token-id or --market"),
+    };
+    let market = resolve_market(&input).await?;
+    let all = parse_outcomes(
+        market.outcomes.as_deref().unwrap_or("[]"),
+        market.clob_token_ids.as_deref().unwrap_or("[]"),
+    )
+    .with_context(|| format!("Market {} has no outcome token IDs", input))?;
+    let outcomes = pick_outcomes(all, &parse_market_ref(&input), outcome.as_deref())
+        .with_context(|| format!("In market: {}", market.question.as_deref().unwrap_or(&input)))?;
+    eprintln!(
+        "market = {}, outcomes = {}",
+        market.slug.as_deref().unwrap_or(&input),
+        outcomes.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>().join(", ")
+    );
+    Ok(MarketOutcomes { market, outcomes })
+}
+
+/// The outcome a token reference points at, the named outcome, or else all of them.
+fn pick_outcomes(
+    outcomes: Vec<(String, String)>,
+    input: &MarketRef,
+    name: Option<&str>,
+) -> Result<Vec<(String, String)>> {
+    match (input, name) {
+        (MarketRef::TokenId(token_id), None) => {
+            let pair = outcomes.into_iter().find(|(_, t)| t == token_id);
+            Ok(vec![pair.with_context(|| format!("Token {} is not an outcome of its market", token_id))?])
+        }
+        (_, Some(name)) => Ok(vec![select_outcome(&outcomes, Some(name))?]),
+        (_, None) => Ok(outcomes),
+    }
+}
+
+/// Split `slug:Outcome` into the market and the outcome. Inputs without a colon, or with
+/// nothing after it, have no outcome.
+pub fn split_market_outcome(input: &str) -> (String, Option<String>) {
+    match input.rsplit_once(':') {
+        Some((market, outcome)) if !market.is_empty() && !outcome.trim().is_empty() => {
+            (market.to_string(), Some(outcome.trim().to_string()))
+        }
+        _ => (input.to_string(), None),
+    }
+}
+
+/// Pick one `(outcome, token_id)` pair by name: an exact case-insensitive match wins,
+/// otherwise a unique prefix. A missing name only works for single-outcome markets.
+pub fn select_outcome(outcomes: &[(String, String)], name: Option<&str>) -> Result<(String, String)> {
+    let names = || outcomes.iter().map(|(o, _)| o.as_str()).collect::<Vec<_>>().join(", ");
+    let Some(name) = name else {
+        anyhow::ensure!(outcomes.len() == 1, "Market has several outcomes ({}); pick one with --outcome", names());
+        return Ok(outcomes[0].clone());
+    };
+    let name = name.to_lowercase();
+    if let Some(pair) = outcomes.iter().find(|(o, _)| o.to_lowercase() == name) {
+        return Ok(pair.clone());
+    }
+    let matches: Vec<&(String, String)> = outcomes.iter().filter(|(o, _)| o.to_lowercase().starts_with(&name)).collect();
+    match matches.as_slice() {
+        [pair] => Ok((*pair).clone()),
+        [] => anyhow::bail!("No outcome named '{}' (outcomes: {})", name, names()),
+        _ => anyhow::bail!("Outcome '{}' is ambiguous (outcomes: {})", name, names()),
+    }
+}
+
+/// Pick one `(outcome, token_id)` pair by its full name, ignoring case. A missing name
+/// only works for single-outcome markets.
+pub fn select_outcome_exact(outcomes: &[(String, String)], name: Option<&str>) -> Result<(String, String)> {
+    let Some(name) = name else {
+        return select_outcome(outcomes, None);
+    };
+    let wanted = name.to_lowercase();
+    outcomes.iter().find(|(o, _)| o.to_lowercase() == wanted).cloned().with_context(|| {
+        let names = outcomes.iter().map(|(o, _)| o.as_str()).collect::<Vec<_>>().join(", ");
+        format!("No outcome named exactly '{}' (outcomes: {}); orders need the full outcome name", name, names)
+    })
+}
+
+/// Condition ID of the market an outcome token belongs to.
+pub async fn condition_id_for_token(token_id: &str) -> Result<String> {
+    let market = resolve_market(token_id).await?;
+    market.condition_id.with_context(|| format!("Market of token {} has no condition ID", token_id))
+}
+
+/// Token ID of the market's "Yes" outcome, or of its first outcome when there is none.
+pub fn yes_token_id(market: &Market) -> Option<String> {
+    let outcomes = parse_outcomes(market.outcomes.as_deref()?, market.clob_token_ids.as_deref()?)?;
+    let (_, token_id) = select_outcome(&outcomes, Some("Yes")).unwrap_or_else(|_| outcomes[0].clone());
+    Some(token_id)
+}
+
+/// Current midpoints of the outcome tokens. Closed markets have no book, so failures
+/// only mean the midpoints are left out.
+async fn fetch_midpoints(outcomes: &[(String, String)]) -> HashMap<String, Decimal> {
+    if outcomes.is_empty() {
+        return HashMap::new();
+    }
+    let requests: Vec<MidpointRequest> = outcomes
+        .iter()
+        .map(|(_, token_id)| MidpointRequest::builder().token_id(token_id.clone()).build())
+        .collect();
+    let result = match ClobClient::new("https://clob.polymarket.com", ClobConfig::default()) {
+        Ok(client) => client.midpoints(&requests).await.map_err(anyhow::Error::from),
+        Err(e) => Err(e.into()),
+    };
+    match result {
+        Ok(response) => response.midpoints,
+        Err(e) => {
+            log::warn!("error = {:#}, failed to fetch midpoints", e);
+            HashMap::new()
+        }
+    }
+}
+
+pub fn market_status(market: &Market) -> String {
+    let mut status = Vec::new();
+    if market.archived.unwrap_or(false) {
+        status.push("archived");
+    } else if market.closed.unwrap_or(false) {
+        status.push("closed");
+    } else if market.active.unwrap_or(false) {
+        status.push("active");
+    } else {
+        status.push("inactive");
+    }
+    if market.accepting_orders.unwrap_or(false) {
+        status.push("accepting orders");
+    }
+    status.join(", ")
+}
+
+fn display_decimal(value: Option<Decimal>) -> String {
+    value.map(|v| v.normalize().to_string()).unwrap_or_else(|| "-".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_market_ref_condition_id() {
+        let condition = "0xdd22472e552920b8438158ea7238bfadfa4f736aa4cee91a6b86c39ead110917";
+        assert_eq!(parse_market_ref(condition), MarketRef::ConditionId(condition.to_string()));
+    }
+
+    #[test]
+    fn test_parse_market_ref_numbers() {
+        assert_eq!(parse_market_ref("516710"), MarketRef::Id("516710".to_string()));
+        let token = "71321045679252212594626385532706912750332728571942532289631379312455583992563";
+        assert_eq!(parse_market_ref(token), MarketRef::TokenId(token.to_string()));
+    }
+
+    #[test]
+    fn test_parse_market_ref_slug() {
+        assert_eq!(
+            parse_market_ref("will-bitcoin-hit-100k"),
+            MarketRef::Slug("will-bitcoin-hit-100k".to_string())
+        );
+        // Short hex strings aren't condition IDs
+        assert_eq!(parse_market_ref("0xabc"), MarketRef::Slug("0xabc".to_string()));
+    }
+
+    #[test]
+    fn test_pick_outcomes() {
+        let outcomes = vec![("Yes".to_string(), "111".to_string()), ("No".to_string(), "222".to_string())];
+        let slug = MarketRef::Slug("will-it-rain".to_string());
+        assert_eq!(pick_outcomes(outcomes.clone(), &slug, None).unwrap(), outcomes);
+        assert_eq!(pick_outcomes(outcomes.clone(), &slug, Some("n")).unwrap(), [outcomes[1].clone()]);
+        let token = MarketRef::TokenId("222".to_string());
+        assert_eq!(pick_outcomes(outcomes.clone(), &token, None).unwrap(), [outcomes[1].clone()]);
+        assert!(pick_outcomes(outcomes, &MarketRef::TokenId("333".to_string()), None).is_err());
+    }
+
+    #[test]
+    fn test_split_market_outcome() {
+        assert_eq!(split_market_outcome("will-it-rain:Yes"), ("will-it-rain".to_string(), Some("Yes".to_string())));
+        assert_eq!(split_market_outcome("will-it-rain"), ("will-it-rain".to_string(), None));
+        assert_eq!(split_market_outcome("will-it-rain:"), ("will-it-rain:".to_string(), None));
+    }
+
+    fn outcomes(names: &[&str]) -> Vec<(String, String)> {
+        names.iter().enumerate().map(|(i, n)| (n.to_string(), format!("token_{}", i))).collect()
+    }
+
+    #[test]
+    fn test_select_outcome_by_name() {
+        let pairs = outcomes(&["Yes", "No"]);
+        assert_eq!(select_outcome(&pairs, Some("yes")).unwrap().1, "token_0");
+        assert_eq!(select_outcome(&pairs, Some("NO")).unwrap().1, "token_1");
+        assert_eq!(select_outcome(&pairs, Some("n")).unwrap().1, "token_1");
+        assert!(select_outcome(&pairs, Some("maybe")).is_err());
+        assert!(select_outcome(&pairs, None).is_err());
+    }
+
+    #[test]
+    fn test_select_outcome_exact() {
+        let pairs = outcomes(&["Yes", "No"]);
+        assert_eq!(select_outcome_exact(&pairs, Some("no")).unwrap().1, "token_1");
+        assert!(select_outcome_exact(&pairs, Some("n")).is_err());
+        assert!(select_outcome_exact(&pairs, Some("y")).is_err());
+        assert!(select_outcome_exact(&pairs, None).is_err());
+        assert_eq!(select_outcome_exact(&outcomes(&["Over"]), None).unwrap().1, "token_0");
+    }
+
+    #[test]
+    fn test_select_outcome_ambiguous_prefix() {
+        let pairs = outcomes(&["Over 2.5", "Over 3.5", "Over"]);
+        assert!(select_outcome(&pairs, Some("over ")).is_err());
+        // An exact match beats prefix matches
+        assert_eq!(select_outcome(&pairs, Some("over")).unwrap().1, "token_2");
+    }
+}
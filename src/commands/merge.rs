@@ -0,0 +1,150 @@
+use anyhow::{Context, Result};
+use alloy::primitives::{B256, Bytes, U256};
+use alloy::providers::ProviderBuilder;
+use alloy::sol_types::SolCall;
+use polymarket_client_sdk::{
+    POLYGON, PRIVATE_KEY_VAR,
+    auth::{LocalSigner, Signer},
+};
+use std::env;
+use std::str::FromStr;
+
+use crate::commands::split::{SetContracts, binary_partition, fetch_binary_market, read_set_state};
+use crate::commands::status::{format_balance, parse_balance};
+use crate::constants::USDC_E_ADDRESS;
+use crate::contracts::{IConditionalTokens, IERC1155, INegRiskAdapter};
+use crate::rpc;
+use crate::wallet::{funder_address, send_call, signature_type_from_env};
+
+pub async fn execute(market: String, amount: Option<String>, max: bool, dry_run: bool, rpc_urls: &[String]) -> Result<()> {
+    let requested = match (&amount, max) {
+        (Some(amount), false) => Some(parse_balance(amount)?),
+        (None, true) => None,
+        _ => anyhow::bail!("Pass either --amount or --max"),
+    };
+
+    let private_key = env::var(PRIVATE_KEY_VAR).context("Need PRIVATE_KEY environment variable")?;
+    let signer = LocalSigner::from_str(&private_key)?.with_chain_id(Some(POLYGON));
+    let owner = signer.address();
+    let signature_type = signature_type_from_env()?;
+    let holder = funder_address(owner, signature_type)?;
+    println!("wallet = {}, holder = {}, signature_type = {}", owner, holder, signature_type);
+
+    let market = fetch_binary_market(&market).await?;
+    println!("market = {}, condition = {}, neg_risk = {}", market.question, market.condition_id, market.neg_risk);
+
+    let provider = ProviderBuilder::new()
+        .wallet(signer.clone())
+        .connect_client(rpc::connect(rpc_urls).await?);
+
+    let contracts = SetContracts::polygon()?;
+    let operator = contracts.operator(market.neg_risk);
+
+    let state = read_set_state(&provider, &contracts, holder, operator, &market).await?;
+    println!(
+        "usdc = ${}, yes = {}, no = {}",
+        format_balance(state.collateral),
+        format_balance(state.balances[0]),
+        format_balance(state.balances[1])
+    );
+
+    let mergeable = mergeable_amount(&state.balances);
+    let amount = requested.unwrap_or(mergeable);
+    anyhow::ensure!(!amount.is_zero(), "No complete sets to merge: need both outcome tokens");
+    anyhow::ensure!(
+        amount <= mergeable,
+        "Not enough complete sets: can merge at most {}, asked for {}",
+        format_balance(mergeable),
+        format_balance(amount)
+    );
+
+    // The adapter moves outcome tokens on the holder's behalf; the CTF burns them directly.
+    if market.neg_risk && !state.approved_for_all {
+        let data: Bytes = IERC1155::setApprovalForAllCall { operator, approved: true }.abi_encode().into();
+        if dry_run {
+            println!("would approve {} for outcome tokens", operator);
+        } else {
+            let tx_hash = send_call(&provider, owner, signature_type, contracts.conditional_tokens, data).await?;
+            println!("tx = {}, approved {} for outcome tokens", tx_hash, operator);
+        }
+    }
+
+    let data = merge_calldata(market.condition_id, market.neg_risk, amount);
+    if dry_run {
+        println!(
+            "would merge {} yes + {} no into ${} via {}",
+            format_balance(amount),
+            format_balance(amount),
+            format_balance(amount),
+            contracts.label(market.neg_risk)
+        );
+        return Ok(());
+    }
+
+    let tx_hash = send_call(&provider, owner, signature_type, operator, data).await?;
+    println!("tx = {}, merged", tx_hash);
+
+    let after = read_set_state(&provider, &contracts, holder, operator, &market).await?;
+    println!(
+        "usdc = ${}, yes = {}, no = {}",
+        format_balance(after.collateral),
+        format_balance(after.balances[0]),
+        format_balance(after.balances[1])
+    );
+
+    Ok(())
+}
+
+/// Number of complete sets held: the smaller of the two outcome balances.
+pub fn mergeable_amount(balances: &[U256; 2]) -> U256 {
+    balances[0].min(balances[1])
+}
+
+/// Calldata merging `amount` complete sets back into USDC.e.
+pub fn merge_calldata(condition_id: B256, neg_risk: bool, amount: U256) -> Bytes {
+    if neg_risk {
+        INegRiskAdapter::mergePositionsCall { conditionId: condition_id, amount }.abi_encode().into()
+    } else {
+        IConditionalTokens::mergePositionsCall {
+            collateralToken: USDC_E_ADDRESS,
+            parentCollectionId: B256::ZERO,
+            conditionId: condition_id,
+            partition: binary_partition(),
+            amount,
+        }
+        .abi_encode()
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONDITION: &str = "0xdd22472e552920b8438158ea7238bfadfa4f736aa4cee91a6b86c39ead110917";
+
+    #[test]
+    fn test_mergeable_amount_is_smaller_side() {
+        assert_eq!(mergeable_amount(&[U256::from(7u64), U256::from(3u64)]), U256::from(3u64));
+        assert_eq!(mergeable_amount(&[U256::ZERO, U256::from(3u64)]), U256::ZERO);
+    }
+
+    #[test]
+    fn test_merge_calldata_ctf() {
+        let condition_id = B256::from_str(CONDITION).unwrap();
+        let data = merge_calldata(condition_id, false, U256::from(2_000_000u64));
+        let decoded = IConditionalTokens::mergePositionsCall::abi_decode(&data).unwrap();
+        assert_eq!(decoded.conditionId, condition_id);
+        assert_eq!(decoded.partition, binary_partition());
+        assert_eq!(decoded.amount, U256::from(2_000_000u64));
+    }
+
+    #[test]
+    fn test_merge_calldata_neg_risk() {
+        let condition_id = B256::from_str(CONDITION).unwrap();
+        let data = merge_calldata(condition_id, true, U256::from(2_000_000u64));
+        let decoded = INegRiskAdapter::mergePositionsCall::abi_decode(&data).unwrap();
+        assert_eq!(decoded.conditionId, condition_id);
+        assert_eq!(decoded.amount, U256::from(2_000_000u64));
+    }
+}
@@ -1,9 +1,24 @@
 pub mod search;
+pub mod market;
+pub mod event;
 pub mod positions;
 pub mod orderbook;
 pub mod trade;
 pub mod midpoint;
+pub mod history;
 pub mod order;
 pub mod status;
 pub mod approve;
 pub mod upgrade;
+pub mod balance;
+pub mod redeem;
+pub mod split;
+pub mod merge;
+pub mod convert;
+pub mod reconcile;
+pub mod ctf;
+pub mod transfer;
+pub mod fills;
+pub mod approvals;
+pub mod screen;
+pub mod scan;
@@ -1,11 +1,29 @@
 use anyhow::{Context, Result};
-use polymarket_client_sdk::clob::{
-    Client as ClobClient, Config as ClobConfig,
-    types::request::{OrderBookSummaryRequest, MidpointRequest, SpreadRequest},
-    types::response::OrderSummary,
+use polymarket_client_sdk::{
+    clob::{
+        Client as ClobClient, Config as ClobConfig,
+        types::request::{OrderBookSummaryRequest, MidpointRequest, SpreadRequest},
+        types::response::{OrderBookSummaryResponse, OrderSummary},
+    },
+    clob::types::Side,
+    types::{DateTime, Decimal},
 };
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
 
-pub async fn execute(token_id: String) -> Result<()> {
+use crate::ws::{BookMessage, MarketMessage, MarketStream, PriceChange, StreamEvent, market_channel_url};
+
+/// Token IDs per `/books` request.
+const BOOKS_BATCH_SIZE: usize = 100;
+/// Levels per side shown by `--watch` without `--depth`.
+const WATCH_LEVELS: usize = 10;
+/// Distances from the mid, in cents, that depth is summed within.
+const DEPTH_BANDS_CENTS: [i64; 3] = [1, 5, 10];
+
+pub async fn execute(token_id: String, depth: Option<usize>, sizes: Vec<String>) -> Result<()> {
+    let targets = sizes.iter().map(|s| parse_size_target(s)).collect::<Result<Vec<_>>>()?;
     let client = ClobClient::new("https://clob.polymarket.com", ClobConfig::default())?;
     let request = OrderBookSummaryRequest::builder().token_id(token_id.clone()).build();
     let book = client.order_book(&request).await.context("Failed to fetch order book")?;
@@ -30,32 +48,510 @@ pub async fn execute(token_id: String) -> Result<()> {
 
     let bids = sort_bids(book.bids);
     let asks = sort_asks(book.asks);
+    let levels = depth.unwrap_or(usize::MAX);
 
     println!("  Bids:");
-    for bid in &bids {
+    for bid in bids.iter().take(levels) {
         println!("    Price: {}, Size: {}", bid.price, bid.size);
     }
 
     println!("  Asks:");
-    for ask in &asks {
+    for ask in asks.iter().take(levels) {
         println!("    Price: {}, Size: {}", ask.price, ask.size);
     }
 
+    println!("  Analytics:");
+    for line in render_analytics(&bids, &asks, &targets) {
+        println!("    {}", line);
+    }
+
     Ok(())
 }
 
+/// Keep a live copy of the book from the WebSocket market channel and redraw it in place
+/// on every change, until interrupted.
+pub async fn watch(token_id: String, depth: Option<usize>) -> Result<()> {
+    let mut stream = MarketStream::new(market_channel_url(), vec![token_id.clone()]);
+    let mut watch = BookWatch::new(token_id.clone());
+    let mut drawn = 0;
+    loop {
+        let event = stream.next().await;
+        match watch.handle(&event) {
+            Ok(true) => drawn = redraw(&watch.render(depth.unwrap_or(WATCH_LEVELS)), drawn)?,
+            Ok(false) => {}
+            Err(desync) => {
+                log::warn!("token = {}, {}, resyncing", token_id, desync);
+                stream.resync();
+            }
+        }
+    }
+}
+
+/// Print `lines` over the `drawn` lines printed last time. Returns how many were printed.
+fn redraw(lines: &[String], drawn: usize) -> Result<usize> {
+    let mut out = std::io::stdout().lock();
+    if drawn > 0 {
+        write!(out, "\x1b[{}A", drawn)?;
+    }
+    for line in lines {
+        // Clear what's left of the previous, possibly longer, line
+        writeln!(out, "{}\x1b[K", line)?;
+    }
+    write!(out, "\x1b[J")?;
+    out.flush()?;
+    Ok(lines.len())
+}
+
 /// Sort bids descending (highest price first).
 pub fn sort_bids(mut bids: Vec<OrderSummary>) -> Vec<OrderSummary> {
-    bids.sort_by(|a, b| b.price.cmp(&a.price));
+    bids.sort_by_key(|b| std::cmp::Reverse(b.price));
     bids
 }
 
 /// Sort asks ascending (lowest price first).
 pub fn sort_asks(mut asks: Vec<OrderSummary>) -> Vec<OrderSummary> {
-    asks.sort_by(|a, b| a.price.cmp(&b.price));
+    asks.sort_by_key(|a| a.price);
     asks
 }
 
+/// Fetch the books of many tokens, in batches, keyed by token ID. Tokens without a book
+/// are missing from the result.
+pub async fn fetch_order_books(
+    client: &ClobClient,
+    token_ids: &[String],
+) -> Result<HashMap<String, OrderBookSummaryResponse>> {
+    let mut books = HashMap::new();
+    for chunk in token_ids.chunks(BOOKS_BATCH_SIZE) {
+        let requests: Vec<OrderBookSummaryRequest> = chunk
+            .iter()
+            .map(|id| OrderBookSummaryRequest::builder().token_id(id.clone()).build())
+            .collect();
+        let batch = client.order_books(&requests).await.context("Failed to fetch order books")?;
+        books.extend(batch.into_iter().map(|book| (book.asset_id.clone(), book)));
+    }
+    Ok(books)
+}
+
+/// Best bid and best ask of a book; either side may be empty.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Quote {
+    pub bid: Option<Decimal>,
+    pub ask: Option<Decimal>,
+}
+
+impl Quote {
+    pub fn from_book(book: &OrderBookSummaryResponse) -> Self {
+        Quote {
+            bid: book.bids.iter().map(|l| l.price).max(),
+            ask: book.asks.iter().map(|l| l.price).min(),
+        }
+    }
+
+    /// Midpoint of a two-sided book.
+    pub fn mid(&self) -> Option<Decimal> {
+        Some((self.bid? + self.ask?) / Decimal::TWO)
+    }
+
+    pub fn spread(&self) -> Option<Decimal> {
+        Some(self.ask? - self.bid?)
+    }
+}
+
+/// Why a local book can no longer be trusted and needs a fresh snapshot.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Desync {
+    /// A change older than the state of the book it would apply to.
+    OutOfOrder { book: i64, change: i64 },
+    /// The server's best bid or ask after a change differs from the local book's.
+    TopOfBook { server: Quote, local: Quote },
+    /// The best bid is at or above the best ask.
+    Crossed(Quote),
+}
+
+impl fmt::Display for Desync {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let price = |p: Option<Decimal>| p.map(|p| p.normalize().to_string()).unwrap_or_else(|| "-".to_string());
+        match self {
+            Desync::OutOfOrder { book, change } => write!(f, "change at {} is older than the book at {}", change, book),
+            Desync::TopOfBook { server, local } => write!(
+                f,
+                "server top of book {}/{} differs from local {}/{}",
+                price(server.bid),
+                price(server.ask),
+                price(local.bid),
+                price(local.ask)
+            ),
+            Desync::Crossed(quote) => write!(f, "book crossed at {}/{}", price(quote.bid), price(quote.ask)),
+        }
+    }
+}
+
+/// A book kept in sync from a snapshot plus price level changes. The channel's hashes
+/// can't be recomputed locally, so consistency is checked against the best bid and ask
+/// the server sends with every change.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalBook {
+    /// Unix time in milliseconds of the last snapshot or change.
+    pub timestamp: i64,
+    pub bids: Vec<OrderSummary>,
+    pub asks: Vec<OrderSummary>,
+}
+
+impl LocalBook {
+    pub fn from_snapshot(book: &BookMessage) -> Self {
+        LocalBook {
+            timestamp: book.timestamp,
+            bids: sort_bids(book.bids.clone()),
+            asks: sort_asks(book.asks.clone()),
+        }
+    }
+
+    pub fn quote(&self) -> Quote {
+        Quote { bid: self.bids.first().map(|l| l.price), ask: self.asks.first().map(|l| l.price) }
+    }
+
+    /// Set the size of one price level, removing it at zero.
+    pub fn apply(&mut self, timestamp: i64, change: &PriceChange) -> Result<(), Desync> {
+        if timestamp < self.timestamp {
+            return Err(Desync::OutOfOrder { book: self.timestamp, change: timestamp });
+        }
+        self.timestamp = timestamp;
+        let levels = match change.side {
+            Side::Buy => &mut self.bids,
+            Side::Sell => &mut self.asks,
+            _ => return Ok(()),
+        };
+        levels.retain(|l| l.price != change.price);
+        if change.size > Decimal::ZERO {
+            levels.push(OrderSummary::builder().price(change.price).size(change.size).build());
+        }
+        match change.side {
+            Side::Buy => self.bids = sort_bids(std::mem::take(&mut self.bids)),
+            _ => self.asks = sort_asks(std::mem::take(&mut self.asks)),
+        }
+
+        let local = self.quote();
+        if let (Some(bid), Some(ask)) = (local.bid, local.ask) {
+            if bid >= ask {
+                return Err(Desync::Crossed(local));
+            }
+        }
+        // An empty side is reported as a price of zero
+        let server = Quote {
+            bid: change.best_bid.filter(|p| !p.is_zero()),
+            ask: change.best_ask.filter(|p| !p.is_zero()),
+        };
+        let differs = |server: Option<Decimal>, local: Option<Decimal>| server.is_some() && server != local;
+        if differs(server.bid, local.bid) || differs(server.ask, local.ask) {
+            return Err(Desync::TopOfBook { server, local });
+        }
+        Ok(())
+    }
+}
+
+/// State of `order-book --watch`: the local book of one token plus connection counters.
+#[derive(Debug, Clone)]
+pub struct BookWatch {
+    pub token_id: String,
+    pub book: Option<LocalBook>,
+    pub connects: u32,
+    pub resyncs: u32,
+}
+
+impl BookWatch {
+    pub fn new(token_id: String) -> Self {
+        BookWatch { token_id, book: None, connects: 0, resyncs: 0 }
+    }
+
+    /// Update the book from one stream event. Returns whether it changed, or why it fell
+    /// out of sync, in which case it is dropped until the next snapshot.
+    pub fn handle(&mut self, event: &StreamEvent) -> Result<bool, Desync> {
+        match event {
+            StreamEvent::Connected => {
+                self.connects += 1;
+                self.book = None;
+                Ok(false)
+            }
+            StreamEvent::Message(MarketMessage::Book(snapshot)) if snapshot.asset_id == self.token_id => {
+                self.book = Some(LocalBook::from_snapshot(snapshot));
+                Ok(true)
+            }
+            StreamEvent::Message(MarketMessage::PriceChange(message)) => {
+                // Changes before the first snapshot have nothing to apply to
+                let Some(book) = self.book.as_mut() else {
+                    return Ok(false);
+                };
+                let mut changed = false;
+                for change in message.price_changes.iter().filter(|c| c.asset_id == self.token_id) {
+                    if let Err(desync) = book.apply(message.timestamp, change) {
+                        self.book = None;
+                        self.resyncs += 1;
+                        return Err(desync);
+                    }
+                    changed = true;
+                }
+                Ok(changed)
+            }
+            StreamEvent::Message(_) => Ok(false),
+        }
+    }
+
+    /// The book in the layout of a one-off `order-book`, `levels` deep on each side.
+    pub fn render(&self, levels: usize) -> Vec<String> {
+        let Some(book) = &self.book else {
+            return vec![format!("Order Book for {}: waiting for snapshot", self.token_id)];
+        };
+        let quote = book.quote();
+        let na = |p: Option<Decimal>| p.map(|p| p.normalize().to_string()).unwrap_or_else(|| "N/A".to_string());
+        let updated = DateTime::from_timestamp_millis(book.timestamp)
+            .map(|t| t.format("%Y-%m-%d %H:%M:%S%.3f").to_string())
+            .unwrap_or_else(|| book.timestamp.to_string());
+        let mut lines = vec![
+            format!("Order Book for {} (live):", self.token_id),
+            format!(
+                "  updated = {}, reconnects = {}, resyncs = {}",
+                updated,
+                self.connects.saturating_sub(1),
+                self.resyncs
+            ),
+            format!("  Midpoint Price: {}", na(quote.mid())),
+            format!("  Spread: {}", na(quote.spread())),
+            "  Bids:".to_string(),
+        ];
+        lines.extend(book.bids.iter().take(levels).map(|l| format!("    Price: {}, Size: {}", l.price, l.size)));
+        lines.push("  Asks:".to_string());
+        lines.extend(book.asks.iter().take(levels).map(|l| format!("    Price: {}, Size: {}", l.price, l.size)));
+        lines
+    }
+}
+
+/// Liquidity on both sides of a book: shares, and their USDC value at the level prices.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Depth {
+    pub bid_size: Decimal,
+    pub bid_notional: Decimal,
+    pub ask_size: Decimal,
+    pub ask_notional: Decimal,
+}
+
+impl Depth {
+    pub fn from_levels<'a>(
+        bids: impl IntoIterator<Item = &'a OrderSummary>,
+        asks: impl IntoIterator<Item = &'a OrderSummary>,
+    ) -> Self {
+        let mut depth = Depth::default();
+        for level in bids {
+            depth.bid_size += level.size;
+            depth.bid_notional += level.size * level.price;
+        }
+        for level in asks {
+            depth.ask_size += level.size;
+            depth.ask_notional += level.size * level.price;
+        }
+        depth
+    }
+
+    /// Levels at most `band` away from `mid` on either side.
+    pub fn within(bids: &[OrderSummary], asks: &[OrderSummary], mid: Decimal, band: Decimal) -> Self {
+        Depth::from_levels(
+            bids.iter().filter(|l| l.price >= mid - band),
+            asks.iter().filter(|l| l.price <= mid + band),
+        )
+    }
+
+    /// `(bids - asks) / (bids + asks)` in shares: 1 is all bids, -1 all asks.
+    pub fn imbalance(&self) -> Option<Decimal> {
+        let total = self.bid_size + self.ask_size;
+        (!total.is_zero()).then(|| (self.bid_size - self.ask_size) / total)
+    }
+}
+
+/// The mid weighted by the size on the opposite side of the top of the book, which leans
+/// towards the side more likely to be taken out next. Takes sorted levels, best first.
+pub fn microprice(bids: &[OrderSummary], asks: &[OrderSummary]) -> Option<Decimal> {
+    let (bid, ask) = (bids.first()?, asks.first()?);
+    let total = bid.size + ask.size;
+    (!total.is_zero()).then(|| (bid.price * ask.size + ask.price * bid.size) / total)
+}
+
+/// An order size: a number of shares, or a USDC amount written as `$N`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SizeTarget {
+    Shares(Decimal),
+    Notional(Decimal),
+}
+
+impl fmt::Display for SizeTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SizeTarget::Shares(shares) => write!(f, "{} shares", shares.normalize()),
+            SizeTarget::Notional(amount) => write!(f, "${}", amount.normalize()),
+        }
+    }
+}
+
+pub fn parse_size_target(value: &str) -> Result<SizeTarget> {
+    let value = value.trim();
+    let (amount, notional) = match value.strip_prefix('$') {
+        Some(amount) => (amount, true),
+        None => (value, false),
+    };
+    let amount = Decimal::from_str(amount.trim())
+        .ok()
+        .filter(|a| *a > Decimal::ZERO)
+        .with_context(|| format!("Invalid size: {} (use shares like 100, or USDC like $100)", value))?;
+    Ok(if notional { SizeTarget::Notional(amount) } else { SizeTarget::Shares(amount) })
+}
+
+/// Taking liquidity from one side of a book.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sweep {
+    pub shares: Decimal,
+    pub notional: Decimal,
+    /// Price of the last level touched.
+    pub worst_price: Option<Decimal>,
+    /// Whether the book was deep enough for the whole size.
+    pub complete: bool,
+}
+
+impl Sweep {
+    pub fn vwap(&self) -> Option<Decimal> {
+        (!self.shares.is_zero()).then(|| self.notional / self.shares)
+    }
+}
+
+/// Walk sorted levels, best first, until `target` is filled or the book runs out.
+pub fn sweep(levels: &[OrderSummary], target: SizeTarget) -> Sweep {
+    let mut fill = Sweep { shares: Decimal::ZERO, notional: Decimal::ZERO, worst_price: None, complete: false };
+    for level in levels.iter().filter(|l| l.size > Decimal::ZERO && l.price > Decimal::ZERO) {
+        fill.worst_price = Some(level.price);
+        // Decide completion on what's left rather than the running total, which a
+        // dollar target's share division can leave a rounding error short of it
+        let (shares, notional, complete) = match target {
+            SizeTarget::Shares(target) => {
+                let remaining = target - fill.shares;
+                if remaining <= level.size {
+                    (remaining, remaining * level.price, true)
+                } else {
+                    (level.size, level.size * level.price, false)
+                }
+            }
+            SizeTarget::Notional(target) => {
+                let remaining = target - fill.notional;
+                if remaining <= level.size * level.price {
+                    (remaining / level.price, remaining, true)
+                } else {
+                    (level.size, level.size * level.price, false)
+                }
+            }
+        };
+        fill.shares += shares;
+        fill.notional += notional;
+        if complete {
+            fill.complete = true;
+            break;
+        }
+    }
+    fill
+}
+
+/// Microprice, depth bands around the mid, imbalance, and the cost of buying and selling
+/// each target size. Takes sorted levels, best first.
+pub fn render_analytics(bids: &[OrderSummary], asks: &[OrderSummary], targets: &[SizeTarget]) -> Vec<String> {
+    let show = |d: Option<Decimal>, dp: u32| {
+        d.map(|d| d.round_dp(dp).normalize().to_string()).unwrap_or_else(|| "-".to_string())
+    };
+    let quote = Quote { bid: bids.first().map(|l| l.price), ask: asks.first().map(|l| l.price) };
+    let top = Depth::from_levels(bids.first(), asks.first());
+    let mut lines = vec![format!(
+        "microprice = {}, top_imbalance = {}",
+        show(microprice(bids, asks), 4),
+        show(top.imbalance(), 4)
+    )];
+
+    if let Some(mid) = quote.mid() {
+        for cents in DEPTH_BANDS_CENTS {
+            let depth = Depth::within(bids, asks, mid, Decimal::new(cents, 2));
+            lines.push(format!(
+                "within {}¢: bid_size = {}, bid_usd = {}, ask_size = {}, ask_usd = {}, imbalance = {}",
+                cents,
+                depth.bid_size.normalize(),
+                depth.bid_notional.round_dp(2).normalize(),
+                depth.ask_size.normalize(),
+                depth.ask_notional.round_dp(2).normalize(),
+                show(depth.imbalance(), 4)
+            ));
+        }
+    }
+
+    for target in targets {
+        for (side, levels) in [("buy", asks), ("sell", bids)] {
+            let fill = sweep(levels, *target);
+            // Cost of the sweep per share against the mid
+            let slippage = match (fill.vwap(), quote.mid()) {
+                (Some(vwap), Some(mid)) if side == "buy" => Some(vwap - mid),
+                (Some(vwap), Some(mid)) => Some(mid - vwap),
+                _ => None,
+            };
+            lines.push(format!(
+                "{} {}: vwap = {}, shares = {}, usd = {}, worst_price = {}, slippage = {}{}",
+                side,
+                target,
+                show(fill.vwap(), 4),
+                fill.shares.round_dp(2).normalize(),
+                fill.notional.round_dp(2).normalize(),
+                show(fill.worst_price, 4),
+                show(slippage, 4),
+                if fill.complete { "" } else { " (book too thin)" }
+            ));
+        }
+    }
+    lines
+}
+
+/// Complete sets bought by walking several ask books together.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BasketFill {
+    /// Shares bought of every leg.
+    pub sets: Decimal,
+    /// Total USDC paid across all legs.
+    pub cost: Decimal,
+    /// Price of one set at the top of the books.
+    pub best_set_cost: Decimal,
+}
+
+/// Buy one share of every leg at a time, always from the best remaining ask of each book,
+/// for as long as a set costs less than `max_set_cost`. Returns `None` when not even the
+/// first set is cheap enough or a leg has no asks.
+pub fn walk_basket(legs: &[Vec<OrderSummary>], max_set_cost: Decimal) -> Option<BasketFill> {
+    let mut books: Vec<Vec<OrderSummary>> = legs.iter().map(|asks| sort_asks(asks.clone())).collect();
+    for book in &mut books {
+        book.retain(|level| level.size > Decimal::ZERO);
+    }
+    let mut levels = vec![0usize; books.len()];
+    let mut remaining: Vec<Decimal> = books.iter().map(|b| b.first().map(|l| l.size).unwrap_or_default()).collect();
+    let mut fill: Option<BasketFill> = None;
+
+    while !books.is_empty() && books.iter().zip(&levels).all(|(book, &i)| i < book.len()) {
+        let set_cost: Decimal = books.iter().zip(&levels).map(|(book, &i)| book[i].price).sum();
+        if set_cost >= max_set_cost {
+            break;
+        }
+        let sets = remaining.iter().copied().min().unwrap_or_default();
+        let fill = fill.get_or_insert(BasketFill { sets: Decimal::ZERO, cost: Decimal::ZERO, best_set_cost: set_cost });
+        fill.sets += sets;
+        fill.cost += sets * set_cost;
+        for (leg, book) in books.iter().enumerate() {
+            remaining[leg] -= sets;
+            if remaining[leg].is_zero() {
+                levels[leg] += 1;
+                remaining[leg] = book.get(levels[leg]).map(|l| l.size).unwrap_or_default();
+            }
+        }
+    }
+    fill
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -116,4 +612,220 @@ mod tests {
         assert_eq!(sorted.len(), 1);
         assert_eq!(sorted[0].price, Decimal::from_str("0.50").unwrap());
     }
+
+    #[test]
+    fn test_quote_from_book() {
+        let book: OrderBookSummaryResponse = serde_json::from_value(serde_json::json!({
+            "market": "0x1",
+            "asset_id": "1",
+            "timestamp": "1700000000000",
+            "bids": [{ "price": "0.40", "size": "10" }, { "price": "0.45", "size": "5" }],
+            "asks": [{ "price": "0.52", "size": "10" }, { "price": "0.50", "size": "5" }],
+            "min_order_size": "5",
+            "neg_risk": false,
+            "tick_size": "0.01"
+        }))
+        .unwrap();
+        let quote = Quote::from_book(&book);
+        assert_eq!(quote.bid, Some(Decimal::from_str("0.45").unwrap()));
+        assert_eq!(quote.ask, Some(Decimal::from_str("0.50").unwrap()));
+        assert_eq!(quote.mid(), Some(Decimal::from_str("0.475").unwrap()));
+        let one_sided = Quote { bid: quote.bid, ask: None };
+        assert_eq!(one_sided.mid(), None);
+    }
+
+    #[test]
+    fn test_walk_basket_stops_at_max_cost() {
+        let legs = vec![
+            vec![make_level("0.40", "100"), make_level("0.45", "50")],
+            vec![make_level("0.50", "30"), make_level("0.52", "200")],
+        ];
+        // 30 sets at 0.90, 70 at 0.92, then 0.45 + 0.52 = 0.97 is too much
+        let fill = walk_basket(&legs, Decimal::from_str("0.95").unwrap()).unwrap();
+        assert_eq!(fill.sets, Decimal::from(100));
+        assert_eq!(fill.cost, Decimal::from_str("91.4").unwrap());
+        assert_eq!(fill.best_set_cost, Decimal::from_str("0.90").unwrap());
+    }
+
+    #[test]
+    fn test_walk_basket_runs_out_of_depth() {
+        let legs = vec![vec![make_level("0.20", "10")], vec![make_level("0.30", "25")]];
+        let fill = walk_basket(&legs, Decimal::ONE).unwrap();
+        assert_eq!(fill.sets, Decimal::from(10));
+        assert_eq!(fill.cost, Decimal::from(5));
+    }
+
+    #[test]
+    fn test_walk_basket_no_opportunity() {
+        let legs = vec![vec![make_level("0.60", "10")], vec![make_level("0.50", "10")]];
+        assert!(walk_basket(&legs, Decimal::ONE).is_none());
+        // A leg without asks can't be completed
+        assert!(walk_basket(&[vec![make_level("0.10", "10")], vec![]], Decimal::ONE).is_none());
+    }
+
+    fn book_message(bids: &[(&str, &str)], asks: &[(&str, &str)]) -> BookMessage {
+        let levels = |levels: &[(&str, &str)]| -> Vec<serde_json::Value> {
+            levels.iter().map(|(p, s)| serde_json::json!({ "price": p, "size": s })).collect()
+        };
+        serde_json::from_value(serde_json::json!({
+            "asset_id": "1",
+            "timestamp": "1000",
+            "bids": levels(bids),
+            "asks": levels(asks)
+        }))
+        .unwrap()
+    }
+
+    fn change(side: &str, price: &str, size: &str, best: Option<(&str, &str)>) -> PriceChange {
+        serde_json::from_value(serde_json::json!({
+            "asset_id": "1",
+            "price": price,
+            "size": size,
+            "side": side,
+            "best_bid": best.map(|b| b.0),
+            "best_ask": best.map(|b| b.1)
+        }))
+        .unwrap()
+    }
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn test_local_book_applies_changes_in_order() {
+        let mut book = LocalBook::from_snapshot(&book_message(&[("0.40", "10"), ("0.45", "5")], &[("0.55", "7")]));
+        assert_eq!(book.bids[0].price, dec("0.45"));
+
+        book.apply(1001, &change("BUY", "0.47", "3", Some(("0.47", "0.55")))).unwrap();
+        book.apply(1002, &change("SELL", "0.55", "0", Some(("0.47", "0")))).unwrap();
+        book.apply(1003, &change("BUY", "0.40", "12", None)).unwrap();
+        let prices: Vec<Decimal> = book.bids.iter().map(|l| l.price).collect();
+        assert_eq!(prices, [dec("0.47"), dec("0.45"), dec("0.40")]);
+        assert_eq!(book.bids[2].size, dec("12"));
+        assert!(book.asks.is_empty());
+        assert_eq!(book.timestamp, 1003);
+    }
+
+    #[test]
+    fn test_local_book_detects_desync() {
+        let snapshot = book_message(&[("0.45", "5")], &[("0.55", "7")]);
+        let mut book = LocalBook::from_snapshot(&snapshot);
+        assert_eq!(
+            book.apply(999, &change("BUY", "0.46", "1", None)),
+            Err(Desync::OutOfOrder { book: 1000, change: 999 })
+        );
+
+        // The server saw a level at 0.50 that never reached us
+        let mut book = LocalBook::from_snapshot(&snapshot);
+        let err = book.apply(1001, &change("BUY", "0.44", "1", Some(("0.50", "0.55")))).unwrap_err();
+        assert!(matches!(err, Desync::TopOfBook { .. }));
+
+        let mut book = LocalBook::from_snapshot(&snapshot);
+        let err = book.apply(1001, &change("BUY", "0.60", "1", None)).unwrap_err();
+        assert_eq!(err, Desync::Crossed(Quote { bid: Some(dec("0.60")), ask: Some(dec("0.55")) }));
+    }
+
+    #[test]
+    fn test_book_watch_resyncs_and_waits_for_snapshot() {
+        let snapshot = StreamEvent::Message(MarketMessage::Book(book_message(&[("0.45", "5")], &[("0.55", "7")])));
+        let price_change = |c: PriceChange, timestamp: i64| {
+            let message = serde_json::json!({ "timestamp": timestamp.to_string(), "price_changes": [] });
+            let mut message: crate::ws::PriceChangeMessage = serde_json::from_value(message).unwrap();
+            message.price_changes.push(c);
+            StreamEvent::Message(MarketMessage::PriceChange(message))
+        };
+
+        let mut watch = BookWatch::new("1".to_string());
+        assert_eq!(watch.handle(&StreamEvent::Connected), Ok(false));
+        assert_eq!(watch.handle(&price_change(change("BUY", "0.46", "1", None), 1001)), Ok(false));
+        assert_eq!(watch.handle(&snapshot), Ok(true));
+        assert_eq!(watch.handle(&price_change(change("BUY", "0.46", "1", Some(("0.46", "0.55"))), 1001)), Ok(true));
+        assert!(watch.handle(&price_change(change("SELL", "0.56", "1", Some(("0.46", "0.50"))), 1002)).is_err());
+        assert_eq!(watch.resyncs, 1);
+        assert!(watch.book.is_none());
+
+        watch.handle(&StreamEvent::Connected).unwrap();
+        watch.handle(&snapshot).unwrap();
+        let lines = watch.render(10);
+        assert_eq!(lines[1], "  updated = 1970-01-01 00:00:01.000, reconnects = 1, resyncs = 1");
+        assert_eq!(lines[2], "  Midpoint Price: 0.5");
+        assert_eq!(lines[3], "  Spread: 0.1");
+        assert_eq!(lines[5], "    Price: 0.45, Size: 5");
+    }
+
+    #[test]
+    fn test_depth_within_bands_and_imbalance() {
+        let bids = sort_bids(vec![make_level("0.49", "100"), make_level("0.45", "200"), make_level("0.38", "500")]);
+        let asks = sort_asks(vec![make_level("0.51", "50"), make_level("0.60", "300")]);
+        let mid = dec("0.50");
+        let near = Depth::within(&bids, &asks, mid, dec("0.01"));
+        assert_eq!((near.bid_size, near.ask_size), (dec("100"), dec("50")));
+        assert_eq!(near.bid_notional, dec("49"));
+        assert_eq!(near.imbalance().unwrap().round_dp(4), dec("0.3333"));
+        let wide = Depth::within(&bids, &asks, mid, dec("0.10"));
+        assert_eq!((wide.bid_size, wide.ask_size), (dec("300"), dec("350")));
+        assert_eq!(Depth::default().imbalance(), None);
+    }
+
+    #[test]
+    fn test_microprice_leans_to_thin_side() {
+        let bids = [make_level("0.49", "300")];
+        let asks = [make_level("0.51", "100")];
+        // More bids than asks: the ask is more likely to trade next, so the fair price is above the mid
+        assert_eq!(microprice(&bids, &asks), Some(dec("0.505")));
+        assert_eq!(microprice(&bids, &[]), None);
+    }
+
+    #[test]
+    fn test_parse_size_target() {
+        assert_eq!(parse_size_target("100").unwrap(), SizeTarget::Shares(dec("100")));
+        assert_eq!(parse_size_target("$250.5").unwrap(), SizeTarget::Notional(dec("250.5")));
+        assert!(parse_size_target("$").is_err());
+        assert!(parse_size_target("-5").is_err());
+        assert!(parse_size_target("ten").is_err());
+    }
+
+    #[test]
+    fn test_sweep_shares_and_notional() {
+        let asks = sort_asks(vec![make_level("0.50", "100"), make_level("0.60", "100")]);
+        let fill = sweep(&asks, SizeTarget::Shares(dec("150")));
+        assert!(fill.complete);
+        assert_eq!(fill.notional, dec("80"));
+        assert_eq!(fill.vwap().unwrap().round_dp(4), dec("0.5333"));
+        assert_eq!(fill.worst_price, Some(dec("0.60")));
+
+        let fill = sweep(&asks, SizeTarget::Notional(dec("80")));
+        assert!(fill.complete);
+        assert_eq!(fill.shares, dec("150"));
+
+        let fill = sweep(&asks, SizeTarget::Shares(dec("500")));
+        assert!(!fill.complete);
+        assert_eq!(fill.shares, dec("200"));
+    }
+
+    #[test]
+    fn test_sweep_notional_that_does_not_divide_evenly() {
+        // $3 / 0.22 isn't a whole number of shares; the first level still fills it
+        let asks = sort_asks(vec![make_level("0.22", "1000"), make_level("0.90", "1000")]);
+        let fill = sweep(&asks, SizeTarget::Notional(dec("3")));
+        assert!(fill.complete);
+        assert_eq!(fill.notional, dec("3"));
+        assert_eq!(fill.worst_price, Some(dec("0.22")));
+        assert_eq!(fill.vwap().unwrap().round_dp(4), dec("0.22"));
+    }
+
+    #[test]
+    fn test_render_analytics() {
+        let bids = [make_level("0.49", "300")];
+        let asks = [make_level("0.51", "100")];
+        let lines = render_analytics(&bids, &asks, &[SizeTarget::Notional(dec("10"))]);
+        assert_eq!(lines[0], "microprice = 0.505, top_imbalance = 0.5");
+        assert_eq!(lines[1], "within 1¢: bid_size = 300, bid_usd = 147, ask_size = 100, ask_usd = 51, imbalance = 0.5");
+        assert_eq!(
+            lines[4],
+            "buy $10: vwap = 0.51, shares = 19.61, usd = 10, worst_price = 0.51, slippage = 0.01"
+        );
+        assert_eq!(lines.len(), 6);
+    }
 }
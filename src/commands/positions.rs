@@ -4,13 +4,24 @@ use polymarket_client_sdk::{
     auth::LocalSigner,
     data::{
         Client as DataClient,
-        types::request::PositionsRequest,
+        types::{request::PositionsRequest, response::Position},
     },
-    types::Address,
+    types::{Address, Decimal, Utc},
 };
+use futures::{StreamExt, stream};
+use rust_decimal::prelude::ToPrimitive;
 use std::env;
 use std::str::FromStr;
 
+use crate::chart::sparkline;
+use crate::commands::history::fetch_price_series;
+
+/// Window and spacing of the midpoint sparkline in the 24h column.
+const SPARKLINE_WINDOW_SECS: i64 = 86_400;
+const SPARKLINE_FIDELITY_MINUTES: u32 = 60;
+/// Price histories fetched at once.
+const SPARKLINE_CONCURRENCY: usize = 8;
+
 pub async fn execute(user: Option<String>) -> Result<()> {
     let user_addr = resolve_user_address(user)?;
 
@@ -18,21 +29,85 @@ pub async fn execute(user: Option<String>) -> Result<()> {
     let request = PositionsRequest::builder().user(user_addr).limit(50)?.build();
     let positions = client.positions(&request).await.context("Failed to fetch positions")?;
 
+    let now = Utc::now().timestamp();
+    let histories: Vec<Option<Vec<(i64, Decimal)>>> = stream::iter(positions.iter().map(|pos| async move {
+        fetch_price_series(&pos.asset, now - SPARKLINE_WINDOW_SECS, now, SPARKLINE_FIDELITY_MINUTES)
+            .await
+            .inspect_err(|e| log::warn!("token_id = {}, error = {:#}, failed to fetch price history", pos.asset, e))
+            .ok()
+    }))
+    .buffered(SPARKLINE_CONCURRENCY)
+    .collect()
+    .await;
+
+    let trends: Vec<String> = histories
+        .iter()
+        .map(|history| history.as_deref().and_then(sparkline_summary).unwrap_or_else(|| "-".to_string()))
+        .collect();
+    let pnls: Vec<String> = positions.iter().map(|pos| format!("${} ({}%)", pos.cash_pnl, pos.percent_pnl)).collect();
+    let market_width = positions.iter().map(|p| p.title.chars().count()).max().unwrap_or(0).max("Market".len());
+    let outcome_width = positions.iter().map(|p| p.outcome.chars().count()).max().unwrap_or(0).max("Outcome".len());
+    let pnl_width = pnls.iter().map(|p| p.chars().count()).max().unwrap_or(0).max("PnL".len());
+    let trend_width = trends.iter().map(|t| t.chars().count()).max().unwrap_or(0).max("24h".len());
+
     println!("Positions for {}:", user_addr);
-    for pos in positions {
-        println!("- Market: {}", pos.title);
-        println!("  Token ID: {}", pos.asset);
-        println!("  Outcome: {}", pos.outcome);
-        println!("  Size: {}", pos.size);
-        println!("  Avg Price: {}", pos.avg_price);
-        println!("  Current Value: ${}", pos.current_value);
-        println!("  PnL: ${} ({}%)", pos.cash_pnl, pos.percent_pnl);
-        println!("--------------------------------------------------");
+    println!(
+        "{:<mw$}  {:<ow$}  {:>12}  {:>9}  {:>13}  {:>pw$}  {:<tw$}  Token ID",
+        "Market", "Outcome", "Size", "Avg Price", "Current Value", "PnL", "24h",
+        mw = market_width, ow = outcome_width, pw = pnl_width, tw = trend_width
+    );
+    for ((pos, pnl), trend) in positions.iter().zip(&pnls).zip(&trends) {
+        println!(
+            "{:<mw$}  {:<ow$}  {:>12}  {:>9}  {:>13}  {:>pw$}  {:<tw$}  {}",
+            pos.title,
+            pos.outcome,
+            pos.size,
+            pos.avg_price,
+            format!("${}", pos.current_value),
+            pnl,
+            trend,
+            pos.asset,
+            mw = market_width, ow = outcome_width, pw = pnl_width, tw = trend_width
+        );
     }
 
     Ok(())
 }
 
+/// A sparkline of a price series followed by its first and last price, e.g. `▁▃█ 0.41 → 0.45`.
+fn sparkline_summary(points: &[(i64, Decimal)]) -> Option<String> {
+    let (first, last) = (points.first()?.1, points.last()?.1);
+    let prices: Vec<f64> = points.iter().filter_map(|(_, p)| p.to_f64()).collect();
+    Some(format!("{} {} → {}", sparkline(&prices), first.normalize(), last.normalize()))
+}
+
+/// Page size for the data API positions endpoint (its maximum).
+const POSITIONS_PAGE_SIZE: i32 = 500;
+
+/// Fetch every position of a user from the data API, following pagination.
+/// With `redeemable_only`, only positions in resolved markets are returned.
+pub async fn fetch_all_positions(user: Address, redeemable_only: bool) -> Result<Vec<Position>> {
+    let client = DataClient::default();
+    let mut positions = Vec::new();
+    let mut offset = 0;
+    loop {
+        let request = PositionsRequest::builder()
+            .user(user)
+            .maybe_redeemable(redeemable_only.then_some(true))
+            .limit(POSITIONS_PAGE_SIZE)?
+            .offset(offset)?
+            .build();
+        let page = client.positions(&request).await.context("Failed to fetch positions")?;
+        let count = page.len() as i32;
+        positions.extend(page);
+        if count < POSITIONS_PAGE_SIZE {
+            break;
+        }
+        offset += count;
+    }
+    Ok(positions)
+}
+
 /// Resolve the user address from an explicit argument, env var, or private key derivation.
 pub fn resolve_user_address(user: Option<String>) -> Result<Address> {
     if let Some(u) = user {
@@ -72,4 +147,12 @@ mod tests {
         let result = resolve_user_address(None);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_sparkline_summary() {
+        let points: Vec<(i64, Decimal)> =
+            ["0.41", "0.50", "0.45"].iter().enumerate().map(|(t, p)| (t as i64, Decimal::from_str(p).unwrap())).collect();
+        assert_eq!(sparkline_summary(&points).as_deref(), Some("▁█▄ 0.41 → 0.45"));
+        assert_eq!(sparkline_summary(&[]), None);
+    }
 }
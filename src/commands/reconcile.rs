@@ -0,0 +1,199 @@
+use anyhow::{Context, Result};
+use alloy::primitives::U256;
+use alloy::providers::ProviderBuilder;
+use polymarket_client_sdk::{
+    POLYGON, PRIVATE_KEY_VAR,
+    auth::LocalSigner,
+    contract_config,
+    data::types::response::Position,
+    types::{Address, Decimal},
+};
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::commands::positions::{fetch_all_positions, resolve_user_address};
+use crate::commands::status::format_balance;
+use crate::contracts::balance_of_batch;
+use crate::rpc;
+use crate::wallet::{funder_address, signature_type_from_env};
+
+/// On-chain balances below this many shares are reported as dust.
+const DUST_THRESHOLD: Decimal = Decimal::from_parts(1, 0, 0, false, 2);
+
+pub async fn execute(user: Option<String>, rpc_urls: &[String]) -> Result<()> {
+    let account = resolve_holder(user)?;
+    println!("Reconciling positions for {}:", account);
+
+    let positions = fetch_all_positions(account, false).await?;
+    let tokens = collect_tokens(&positions)?;
+    if tokens.is_empty() {
+        println!("No positions reported by the data API.");
+        return Ok(());
+    }
+
+    let provider = ProviderBuilder::new().connect_client(rpc::connect(rpc_urls).await?);
+    let config = contract_config(POLYGON, false).context("Failed to get contract config")?;
+    let ids: Vec<U256> = tokens.iter().map(|t| t.token_id).collect();
+    let balances = balance_of_batch(&provider, config.conditional_tokens, account, &ids).await?;
+
+    let mut counts = [0usize; 5];
+    for (token, raw) in tokens.iter().zip(balances) {
+        let onchain = format_balance(raw);
+        let status = reconcile(token.api_size, onchain);
+        counts[status as usize] += 1;
+        if status == Reconciliation::Match {
+            continue;
+        }
+        println!(
+            "- {} ({}): api = {}, onchain = {}, status = {}",
+            token.title,
+            token.outcome,
+            token.api_size.map(|s| s.normalize().to_string()).unwrap_or_else(|| "-".to_string()),
+            onchain,
+            status
+        );
+        println!("  Token ID: {}", token.token_id);
+    }
+
+    println!(
+        "tokens = {}, matched = {}, dust = {}, missing_from_api = {}, not_on_chain = {}, size_mismatch = {}",
+        tokens.len(),
+        counts[Reconciliation::Match as usize],
+        counts[Reconciliation::Dust as usize],
+        counts[Reconciliation::MissingFromApi as usize],
+        counts[Reconciliation::NotOnChain as usize],
+        counts[Reconciliation::SizeMismatch as usize]
+    );
+
+    Ok(())
+}
+
+/// Positions live in the funding wallet, so without an explicit user the holder is derived
+/// from the private key and signature type.
+fn resolve_holder(user: Option<String>) -> Result<Address> {
+    if user.is_some() || env::var("USER_ADDRESS").is_ok() {
+        return resolve_user_address(user);
+    }
+    let private_key = env::var(PRIVATE_KEY_VAR).context("PRIVATE_KEY or USER_ADDRESS env var not set")?;
+    let signer = LocalSigner::from_str(&private_key).context("Invalid private key")?;
+    funder_address(signer.address(), signature_type_from_env()?)
+}
+
+/// An outcome token to check, with the size the data API reports for it, if any.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenEntry {
+    pub token_id: U256,
+    pub title: String,
+    pub outcome: String,
+    pub api_size: Option<Decimal>,
+}
+
+/// Every token the data API knows about: each position's asset, plus the opposite outcome,
+/// which may be held on-chain without being indexed.
+pub fn collect_tokens(positions: &[Position]) -> Result<Vec<TokenEntry>> {
+    let mut tokens: Vec<TokenEntry> = Vec::new();
+    for pos in positions {
+        let asset = U256::from_str(&pos.asset).context("Invalid token ID")?;
+        match tokens.iter_mut().find(|t| t.token_id == asset) {
+            Some(existing) => existing.api_size = Some(existing.api_size.unwrap_or_default() + pos.size),
+            None => tokens.push(TokenEntry {
+                token_id: asset,
+                title: pos.title.clone(),
+                outcome: pos.outcome.clone(),
+                api_size: Some(pos.size),
+            }),
+        }
+    }
+    for pos in positions {
+        if pos.opposite_asset.is_empty() {
+            continue;
+        }
+        let opposite = U256::from_str(&pos.opposite_asset).context("Invalid opposite token ID")?;
+        if !tokens.iter().any(|t| t.token_id == opposite) {
+            tokens.push(TokenEntry {
+                token_id: opposite,
+                title: pos.title.clone(),
+                outcome: pos.opposite_outcome.clone(),
+                api_size: None,
+            });
+        }
+    }
+    Ok(tokens)
+}
+
+/// How an on-chain balance compares to the data API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reconciliation {
+    Match,
+    /// A positive balance too small to matter, often left over from partial fills, that the
+    /// API doesn't report or reports as it is.
+    Dust,
+    /// Held on-chain but not reported by the API.
+    MissingFromApi,
+    /// Reported by the API but not held on-chain, usually indexer lag after a sale or redemption.
+    NotOnChain,
+    /// Held in both places with different sizes, usually indexer lag.
+    SizeMismatch,
+}
+
+impl fmt::Display for Reconciliation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Reconciliation::Match => "match",
+            Reconciliation::Dust => "dust",
+            Reconciliation::MissingFromApi => "missing from API",
+            Reconciliation::NotOnChain => "not held on-chain (indexer lag?)",
+            Reconciliation::SizeMismatch => "size mismatch (indexer lag?)",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// Compare the API size of a token (`None` if not reported) with its on-chain balance in shares.
+pub fn reconcile(api_size: Option<Decimal>, onchain: Decimal) -> Reconciliation {
+    let api = api_size.unwrap_or_default();
+    // Only unreported or matching dust; a tiny balance against a real API size is a discrepancy
+    let dust = onchain > Decimal::ZERO && onchain < DUST_THRESHOLD && (api.is_zero() || api == onchain);
+    if dust {
+        Reconciliation::Dust
+    } else if api == onchain {
+        Reconciliation::Match
+    } else if api.is_zero() {
+        Reconciliation::MissingFromApi
+    } else if onchain.is_zero() {
+        Reconciliation::NotOnChain
+    } else {
+        Reconciliation::SizeMismatch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(s: &str) -> Decimal {
+        Decimal::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_reconcile_match() {
+        assert_eq!(reconcile(Some(dec("12.5")), dec("12.5")), Reconciliation::Match);
+        assert_eq!(reconcile(None, Decimal::ZERO), Reconciliation::Match);
+    }
+
+    #[test]
+    fn test_reconcile_dust() {
+        assert_eq!(reconcile(None, dec("0.000001")), Reconciliation::Dust);
+        assert_eq!(reconcile(Some(dec("0.005")), dec("0.005")), Reconciliation::Dust);
+        assert_eq!(reconcile(Some(Decimal::ZERO), dec("0.005")), Reconciliation::Dust);
+        assert_eq!(reconcile(Some(dec("3")), dec("0.005")), Reconciliation::SizeMismatch);
+    }
+
+    #[test]
+    fn test_reconcile_discrepancies() {
+        assert_eq!(reconcile(None, dec("3")), Reconciliation::MissingFromApi);
+        assert_eq!(reconcile(Some(dec("3")), Decimal::ZERO), Reconciliation::NotOnChain);
+        assert_eq!(reconcile(Some(dec("3")), dec("2")), Reconciliation::SizeMismatch);
+    }
+}
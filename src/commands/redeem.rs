@@ -0,0 +1,304 @@
+use anyhow::{Context, Result};
+use alloy::primitives::{B256, Bytes, U256};
+use alloy::providers::{Provider, ProviderBuilder};
+use alloy::sol_types::SolCall;
+use polymarket_client_sdk::{
+    POLYGON, PRIVATE_KEY_VAR,
+    auth::{LocalSigner, Signer},
+    contract_config,
+    data::types::response::Position,
+    types::Address,
+};
+use std::env;
+use std::str::FromStr;
+
+use crate::commands::positions::fetch_all_positions;
+use crate::commands::status::format_balance;
+use crate::constants::USDC_E_ADDRESS;
+use crate::contracts::{
+    IConditionalTokens, IERC1155, INegRiskAdapter,
+    call3, aggregate3, decode_result, new_erc20,
+};
+use crate::rpc;
+use crate::wallet::{funder_address, send_call, signature_type_from_env};
+
+pub async fn execute(market: Option<String>, dry_run: bool, rpc_urls: &[String]) -> Result<()> {
+    let private_key = env::var(PRIVATE_KEY_VAR).context("Need PRIVATE_KEY environment variable")?;
+    let signer = LocalSigner::from_str(&private_key)?.with_chain_id(Some(POLYGON));
+    let owner = signer.address();
+    let signature_type = signature_type_from_env()?;
+    let holder = funder_address(owner, signature_type)?;
+    println!("wallet = {}, holder = {}, signature_type = {}", owner, holder, signature_type);
+
+    let positions = fetch_all_positions(holder, true).await?;
+    let mut markets = group_redeemable(&positions)?;
+    if let Some(market) = &market {
+        let condition_id = B256::from_str(market).context("Invalid condition ID")?;
+        markets.retain(|m| m.condition_id == condition_id);
+    }
+
+    if markets.is_empty() {
+        println!("No redeemable positions.");
+        return Ok(());
+    }
+
+    let provider = ProviderBuilder::new()
+        .wallet(signer.clone())
+        .connect_client(rpc::connect(rpc_urls).await?);
+
+    let config = contract_config(POLYGON, false).context("Failed to get contract config")?;
+    let neg_risk_config = contract_config(POLYGON, true).context("Failed to get neg risk contract config")?;
+    let adapter = neg_risk_config.neg_risk_adapter.context("No Neg Risk Adapter configured")?;
+
+    let states = read_resolution_states(&provider, config.conditional_tokens, holder, &markets).await?;
+    let usdc = new_erc20(USDC_E_ADDRESS, provider.clone());
+
+    let mut total = U256::ZERO;
+    for (market, state) in markets.iter().zip(&states) {
+        println!("market = {}, condition = {}", market.title, market.condition_id);
+
+        if state.denominator.is_zero() {
+            println!("  not resolved on-chain yet, skipping");
+            continue;
+        }
+        if state.balances.iter().all(|b| b.is_zero()) {
+            println!("  no outcome tokens held on-chain, skipping");
+            continue;
+        }
+
+        let expected = expected_payout(&state.balances, &state.numerators, state.denominator);
+        let (to, data) = if market.neg_risk {
+            (adapter, redeem_neg_risk_calldata(market.condition_id, &state.balances))
+        } else {
+            (config.conditional_tokens, redeem_calldata(market.condition_id))
+        };
+        println!(
+            "  yes = {}, no = {}, expected = ${}, via = {}",
+            format_balance(state.balances[0]),
+            format_balance(state.balances[1]),
+            format_balance(expected),
+            if market.neg_risk { "Neg Risk Adapter" } else { "Conditional Tokens" }
+        );
+
+        if dry_run {
+            println!("  would redeem");
+            total += expected;
+            continue;
+        }
+
+        let before = usdc.balanceOf(holder).call().await.context("Failed to read USDC.e balance")?;
+        match send_call(&provider, owner, signature_type, to, data).await {
+            Ok(tx_hash) => {
+                let after = usdc.balanceOf(holder).call().await.context("Failed to read USDC.e balance")?;
+                let received = after.saturating_sub(before);
+                total += received;
+                println!("  tx = {}, received = ${}, redeemed", tx_hash, format_balance(received));
+            }
+            Err(e) => eprintln!("  error = {:?}, redeem failed", e),
+        }
+    }
+
+    if dry_run {
+        println!("total = ${}, would be received", format_balance(total));
+    } else {
+        println!("total = ${}, received", format_balance(total));
+    }
+
+    Ok(())
+}
+
+/// A resolved market with outcome tokens to redeem.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RedeemableMarket {
+    pub condition_id: B256,
+    pub title: String,
+    pub neg_risk: bool,
+    /// Position IDs of the Yes (index 0) and No (index 1) outcome tokens.
+    pub token_ids: [U256; 2],
+}
+
+/// On-chain payout and holdings for one market.
+struct ResolutionState {
+    denominator: U256,
+    numerators: [U256; 2],
+    balances: [U256; 2],
+}
+
+/// Collapse positions into one entry per market; both outcomes of a market may be listed.
+pub fn group_redeemable(positions: &[Position]) -> Result<Vec<RedeemableMarket>> {
+    let mut markets: Vec<RedeemableMarket> = Vec::new();
+    for pos in positions {
+        let market = redeemable_market(
+            &pos.condition_id,
+            &pos.title,
+            pos.negative_risk,
+            pos.outcome_index,
+            &pos.asset,
+            &pos.opposite_asset,
+        )?;
+        if !markets.iter().any(|m| m.condition_id == market.condition_id) {
+            markets.push(market);
+        }
+    }
+    Ok(markets)
+}
+
+/// Build a market entry from one position, ordering its token IDs by outcome index.
+pub fn redeemable_market(
+    condition_id: &str,
+    title: &str,
+    neg_risk: bool,
+    outcome_index: i32,
+    asset: &str,
+    opposite_asset: &str,
+) -> Result<RedeemableMarket> {
+    let condition_id = B256::from_str(condition_id).context("Invalid condition ID")?;
+    let asset = U256::from_str(asset).context("Invalid token ID")?;
+    let opposite = U256::from_str(opposite_asset).context("Invalid opposite token ID")?;
+    let token_ids = match outcome_index {
+        0 => [asset, opposite],
+        1 => [opposite, asset],
+        other => anyhow::bail!("Unexpected outcome index {} for binary market {}", other, condition_id),
+    };
+    Ok(RedeemableMarket {
+        condition_id,
+        title: title.to_string(),
+        neg_risk,
+        token_ids,
+    })
+}
+
+/// Read payout vectors and outcome token balances for every market in one Multicall3 round-trip.
+async fn read_resolution_states<P: Provider>(
+    provider: &P,
+    conditional_tokens: Address,
+    holder: Address,
+    markets: &[RedeemableMarket],
+) -> Result<Vec<ResolutionState>> {
+    let mut calls = Vec::new();
+    for market in markets {
+        calls.push(call3(conditional_tokens, IConditionalTokens::payoutDenominatorCall { conditionId: market.condition_id }));
+        for index in 0..2u64 {
+            calls.push(call3(conditional_tokens, IConditionalTokens::payoutNumeratorsCall {
+                conditionId: market.condition_id,
+                index: U256::from(index),
+            }));
+        }
+        for token_id in market.token_ids {
+            calls.push(call3(conditional_tokens, IERC1155::balanceOfCall { account: holder, id: token_id }));
+        }
+    }
+
+    let results = aggregate3(provider, calls).await?;
+    let mut states = Vec::new();
+    for chunk in results.chunks(5) {
+        states.push(ResolutionState {
+            denominator: decode_result::<IConditionalTokens::payoutDenominatorCall>(&chunk[0])?,
+            numerators: [
+                decode_result::<IConditionalTokens::payoutNumeratorsCall>(&chunk[1])?,
+                decode_result::<IConditionalTokens::payoutNumeratorsCall>(&chunk[2])?,
+            ],
+            balances: [
+                decode_result::<IERC1155::balanceOfCall>(&chunk[3])?,
+                decode_result::<IERC1155::balanceOfCall>(&chunk[4])?,
+            ],
+        });
+    }
+    Ok(states)
+}
+
+/// Collateral paid out for the given outcome balances: `Σ balance × numerator / denominator`.
+pub fn expected_payout(balances: &[U256; 2], numerators: &[U256; 2], denominator: U256) -> U256 {
+    if denominator.is_zero() {
+        return U256::ZERO;
+    }
+    balances
+        .iter()
+        .zip(numerators)
+        .map(|(balance, numerator)| balance * numerator / denominator)
+        .fold(U256::ZERO, |acc, x| acc + x)
+}
+
+/// `redeemPositions` on the Conditional Tokens contract for both outcomes of a USDC.e market.
+pub fn redeem_calldata(condition_id: B256) -> Bytes {
+    IConditionalTokens::redeemPositionsCall {
+        collateralToken: USDC_E_ADDRESS,
+        parentCollectionId: B256::ZERO,
+        conditionId: condition_id,
+        indexSets: vec![U256::from(1), U256::from(2)],
+    }
+    .abi_encode()
+    .into()
+}
+
+/// `redeemPositions` on the Neg Risk Adapter, which takes the exact Yes/No amounts to redeem.
+pub fn redeem_neg_risk_calldata(condition_id: B256, amounts: &[U256; 2]) -> Bytes {
+    INegRiskAdapter::redeemPositionsCall {
+        conditionId: condition_id,
+        amounts: amounts.to_vec(),
+    }
+    .abi_encode()
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONDITION: &str = "0xdd22472e552920b8438158ea7238bfadfa4f736aa4cee91a6b86c39ead110917";
+
+    #[test]
+    fn test_redeemable_market_orders_tokens_by_outcome() {
+        let yes_held = redeemable_market(CONDITION, "Q", false, 0, "11", "22").unwrap();
+        assert_eq!(yes_held.token_ids, [U256::from(11), U256::from(22)]);
+
+        let no_held = redeemable_market(CONDITION, "Q", false, 1, "22", "11").unwrap();
+        assert_eq!(no_held.token_ids, [U256::from(11), U256::from(22)]);
+        assert_eq!(yes_held.condition_id, no_held.condition_id);
+    }
+
+    #[test]
+    fn test_redeemable_market_rejects_bad_input() {
+        assert!(redeemable_market("0x1234", "Q", false, 0, "11", "22").is_err());
+        assert!(redeemable_market(CONDITION, "Q", false, 2, "11", "22").is_err());
+        assert!(redeemable_market(CONDITION, "Q", false, 0, "abc", "22").is_err());
+    }
+
+    #[test]
+    fn test_expected_payout_winner_takes_all() {
+        let balances = [U256::from(10_000_000u64), U256::from(4_000_000u64)];
+        let payout = expected_payout(&balances, &[U256::from(1), U256::ZERO], U256::from(1));
+        assert_eq!(payout, U256::from(10_000_000u64));
+    }
+
+    #[test]
+    fn test_expected_payout_split_resolution() {
+        let balances = [U256::from(10_000_000u64), U256::from(4_000_000u64)];
+        let payout = expected_payout(&balances, &[U256::from(1), U256::from(1)], U256::from(2));
+        assert_eq!(payout, U256::from(7_000_000u64));
+    }
+
+    #[test]
+    fn test_expected_payout_unresolved() {
+        let balances = [U256::from(10u64), U256::from(4u64)];
+        assert_eq!(expected_payout(&balances, &[U256::ZERO, U256::ZERO], U256::ZERO), U256::ZERO);
+    }
+
+    #[test]
+    fn test_redeem_calldata_index_sets() {
+        let condition_id = B256::from_str(CONDITION).unwrap();
+        let decoded = IConditionalTokens::redeemPositionsCall::abi_decode(&redeem_calldata(condition_id)).unwrap();
+        assert_eq!(decoded.collateralToken, USDC_E_ADDRESS);
+        assert_eq!(decoded.parentCollectionId, B256::ZERO);
+        assert_eq!(decoded.indexSets, vec![U256::from(1), U256::from(2)]);
+    }
+
+    #[test]
+    fn test_redeem_neg_risk_calldata_amounts() {
+        let condition_id = B256::from_str(CONDITION).unwrap();
+        let amounts = [U256::from(5u64), U256::ZERO];
+        let decoded = INegRiskAdapter::redeemPositionsCall::abi_decode(&redeem_neg_risk_calldata(condition_id, &amounts)).unwrap();
+        assert_eq!(decoded.conditionId, condition_id);
+        assert_eq!(decoded.amounts, amounts.to_vec());
+    }
+}
@@ -0,0 +1,301 @@
+use anyhow::{Context, Result};
+use polymarket_client_sdk::{
+    clob::{Client as ClobClient, Config as ClobConfig, types::response::OrderBookSummaryResponse},
+    gamma::{
+        Client as GammaClient,
+        types::{
+            request::EventsRequest,
+            response::{Event, Market},
+        },
+    },
+    types::Decimal,
+};
+use std::collections::HashMap;
+
+use crate::cli::ScanCommand;
+use crate::commands::event::fetch_event;
+use crate::commands::market::select_outcome;
+use crate::commands::orderbook::{BasketFill, fetch_order_books, walk_basket};
+use crate::commands::search::parse_outcomes;
+
+/// Events fetched per Gamma page.
+const EVENTS_PAGE_SIZE: usize = 100;
+
+pub async fn execute(command: ScanCommand) -> Result<()> {
+    match command {
+        ScanCommand::Arb { event, max_events, min_profit } => arb(event, max_events, min_profit).await,
+    }
+}
+
+async fn arb(events: Vec<String>, max_events: usize, min_profit: Decimal) -> Result<()> {
+    let events = if events.is_empty() {
+        fetch_neg_risk_events(max_events).await?
+    } else {
+        let mut fetched = Vec::new();
+        for event in &events {
+            fetched.push(fetch_event(event).await?);
+        }
+        fetched
+    };
+
+    let baskets: Vec<NegRiskEvent> = events.iter().filter_map(NegRiskEvent::from_event).collect();
+    let token_ids: Vec<String> = baskets
+        .iter()
+        .flat_map(|b| b.outcomes.iter().flat_map(|o| [o.yes_token.clone(), o.no_token.clone()]))
+        .collect();
+    println!("events = {}, neg_risk_events = {}, tokens = {}", events.len(), baskets.len(), token_ids.len());
+
+    let client = ClobClient::new("https://clob.polymarket.com", ClobConfig::default())?;
+    let books = fetch_order_books(&client, &token_ids).await?;
+
+    let mut opportunities: Vec<Opportunity> = baskets
+        .iter()
+        .flat_map(|basket| basket.opportunities(&books))
+        .filter(|o| o.profit() >= min_profit)
+        .collect();
+    opportunities.sort_by_key(|o| std::cmp::Reverse(o.profit()));
+
+    for o in &opportunities {
+        println!(
+            "- event = {} ({}), buy = all {}, legs = {}, sets = {}, cost = {}, payout = {}, profit = {}",
+            o.title,
+            o.slug,
+            o.side,
+            o.legs,
+            o.fill.sets.normalize(),
+            o.fill.cost.round_dp(4).normalize(),
+            o.payout().normalize(),
+            o.profit().round_dp(4).normalize()
+        );
+        println!(
+            "  best_set_cost = {}, set_payout = {}, min_order_size = {}{}",
+            o.fill.best_set_cost.normalize(),
+            o.set_payout.normalize(),
+            o.min_order_size.normalize(),
+            if o.fill.sets < o.min_order_size { " (below min order size)" } else { "" }
+        );
+        if o.augmented {
+            println!("  WARNING: augmented neg-risk event; outcomes can still be added, so the payout isn't guaranteed");
+        }
+    }
+    println!("opportunities = {}", opportunities.len());
+
+    Ok(())
+}
+
+/// Active, open neg-risk events, busiest first.
+async fn fetch_neg_risk_events(max_events: usize) -> Result<Vec<Event>> {
+    let client = GammaClient::default();
+    let mut events = Vec::new();
+    let mut offset = 0;
+    while events.len() < max_events {
+        let request = EventsRequest::builder()
+            .active(true)
+            .closed(false)
+            .order(vec!["volume24hr".to_string()])
+            .ascending(false)
+            .limit(EVENTS_PAGE_SIZE as i32)
+            .offset(offset as i32)
+            .build();
+        let page = client.events(&request).await.context("Failed to fetch events")?;
+        let page_len = page.len();
+        events.extend(page.into_iter().filter(|e| e.neg_risk.unwrap_or(false)));
+        if page_len < EVENTS_PAGE_SIZE {
+            break;
+        }
+        offset += page_len;
+    }
+    events.truncate(max_events);
+    Ok(events)
+}
+
+/// The YES and NO token of one open market of a neg-risk event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutcomeTokens {
+    pub yes_token: String,
+    pub no_token: String,
+}
+
+impl OutcomeTokens {
+    pub fn from_market(market: &Market) -> Option<Self> {
+        let outcomes = parse_outcomes(market.outcomes.as_deref()?, market.clob_token_ids.as_deref()?)?;
+        let (_, yes_token) = select_outcome(&outcomes, Some("Yes")).ok()?;
+        let (_, no_token) = select_outcome(&outcomes, Some("No")).ok()?;
+        Some(OutcomeTokens { yes_token, no_token })
+    }
+}
+
+/// A neg-risk event reduced to its open markets. Exactly one of them resolves YES, so a
+/// set of every YES pays 1 and a set of every NO pays `legs - 1`.
+#[derive(Debug, Clone)]
+pub struct NegRiskEvent {
+    pub title: String,
+    pub slug: String,
+    pub augmented: bool,
+    pub outcomes: Vec<OutcomeTokens>,
+}
+
+impl NegRiskEvent {
+    /// `None` for events that aren't neg-risk, have fewer than two open markets, or have
+    /// an open market without both tokens, since the basket couldn't be completed.
+    pub fn from_event(event: &Event) -> Option<Self> {
+        if !event.neg_risk.unwrap_or(false) {
+            return None;
+        }
+        let open: Vec<&Market> = event
+            .markets
+            .as_deref()?
+            .iter()
+            .filter(|m| m.active.unwrap_or(false) && !m.closed.unwrap_or(false))
+            .collect();
+        if open.len() < 2 {
+            return None;
+        }
+        let outcomes = open.into_iter().map(OutcomeTokens::from_market).collect::<Option<Vec<_>>>()?;
+        Some(NegRiskEvent {
+            title: event.title.clone().unwrap_or_default(),
+            slug: event.slug.clone().unwrap_or_else(|| event.id.clone()),
+            augmented: event.neg_risk_augmented.unwrap_or(false),
+            outcomes,
+        })
+    }
+
+    /// Walk the YES and the NO ask books of every outcome for sets cheaper than their payout.
+    pub fn opportunities(&self, books: &HashMap<String, OrderBookSummaryResponse>) -> Vec<Opportunity> {
+        let legs = self.outcomes.len();
+        let sides = [
+            ("YES", Decimal::ONE, self.outcomes.iter().map(|o| &o.yes_token).collect::<Vec<_>>()),
+            ("NO", Decimal::from(legs - 1), self.outcomes.iter().map(|o| &o.no_token).collect::<Vec<_>>()),
+        ];
+        let mut found = Vec::new();
+        for (side, set_payout, tokens) in sides {
+            let Some(leg_books) = tokens.iter().map(|t| books.get(*t)).collect::<Option<Vec<_>>>() else {
+                continue;
+            };
+            let asks: Vec<_> = leg_books.iter().map(|b| b.asks.clone()).collect();
+            if let Some(fill) = walk_basket(&asks, set_payout) {
+                found.push(Opportunity {
+                    title: self.title.clone(),
+                    slug: self.slug.clone(),
+                    side,
+                    legs,
+                    set_payout,
+                    min_order_size: leg_books.iter().map(|b| b.min_order_size).max().unwrap_or_default(),
+                    augmented: self.augmented,
+                    fill,
+                });
+            }
+        }
+        found
+    }
+}
+
+/// Buying `fill.sets` complete sets of one side of an event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Opportunity {
+    pub title: String,
+    pub slug: String,
+    pub side: &'static str,
+    pub legs: usize,
+    pub set_payout: Decimal,
+    pub min_order_size: Decimal,
+    pub augmented: bool,
+    pub fill: BasketFill,
+}
+
+impl Opportunity {
+    pub fn payout(&self) -> Decimal {
+        self.fill.sets * self.set_payout
+    }
+
+    pub fn profit(&self) -> Decimal {
+        self.payout() - self.fill.cost
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn market(id: &str, closed: bool) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "active": true,
+            "closed": closed,
+            "outcomes": "[\"Yes\",\"No\"]",
+            "clobTokenIds": format!("[\"{}y\",\"{}n\"]", id, id)
+        })
+    }
+
+    fn event(markets: Vec<serde_json::Value>) -> Event {
+        serde_json::from_value(serde_json::json!({
+            "id": "1",
+            "title": "Who wins?",
+            "slug": "who-wins",
+            "negRisk": true,
+            "markets": markets
+        }))
+        .unwrap()
+    }
+
+    fn book(token: &str, asks: &[(&str, &str)]) -> (String, OrderBookSummaryResponse) {
+        let asks: Vec<_> = asks.iter().map(|(p, s)| serde_json::json!({ "price": p, "size": s })).collect();
+        let book = serde_json::from_value(serde_json::json!({
+            "market": "0x1",
+            "asset_id": token,
+            "timestamp": "1700000000000",
+            "bids": [],
+            "asks": asks,
+            "min_order_size": "5",
+            "neg_risk": true,
+            "tick_size": "0.01"
+        }))
+        .unwrap();
+        (token.to_string(), book)
+    }
+
+    #[test]
+    fn test_neg_risk_event_skips_closed_markets() {
+        let basket = NegRiskEvent::from_event(&event(vec![market("a", false), market("b", false), market("c", true)]))
+            .unwrap();
+        assert_eq!(basket.outcomes.len(), 2);
+        assert_eq!(basket.outcomes[1], OutcomeTokens { yes_token: "by".to_string(), no_token: "bn".to_string() });
+        assert!(NegRiskEvent::from_event(&event(vec![market("a", false), market("b", true)])).is_none());
+    }
+
+    #[test]
+    fn test_opportunities_yes_and_no() {
+        let basket = NegRiskEvent::from_event(&event(vec![market("a", false), market("b", false), market("c", false)]))
+            .unwrap();
+        let books: HashMap<_, _> = [
+            book("ay", &[("0.30", "10")]),
+            book("by", &[("0.30", "20")]),
+            book("cy", &[("0.35", "5"), ("0.45", "50")]),
+            book("an", &[("0.70", "100")]),
+            book("bn", &[("0.72", "100")]),
+            book("cn", &[("0.66", "100")]),
+        ]
+        .into_iter()
+        .collect();
+        let found = basket.opportunities(&books);
+        assert_eq!(found.len(), 1);
+        // 5 sets at 0.95 before the third leg's next level makes a set cost 1.05
+        let yes = &found[0];
+        assert_eq!(yes.side, "YES");
+        assert_eq!(yes.fill.sets, dec("5"));
+        assert_eq!(yes.profit(), dec("0.25"));
+
+        // All NOs at 2.08 against a payout of 2 is not an opportunity; at 1.96 it is
+        let mut books = books;
+        books.insert("cn".to_string(), book("cn", &[("0.54", "40")]).1);
+        let no = basket.opportunities(&books).into_iter().find(|o| o.side == "NO").unwrap();
+        assert_eq!(no.set_payout, dec("2"));
+        assert_eq!(no.fill.sets, dec("40"));
+        assert_eq!(no.profit(), dec("1.60"));
+    }
+}
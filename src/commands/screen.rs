@@ -0,0 +1,352 @@
+use anyhow::{Context, Result};
+use polymarket_client_sdk::{
+    clob::{
+        Client as ClobClient, Config as ClobConfig,
+        types::request::{MidpointRequest, SpreadRequest},
+    },
+    gamma::{
+        Client as GammaClient,
+        types::{request::MarketsRequest, response::Market},
+    },
+    types::{DateTime, Decimal, Utc},
+};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::commands::market::yes_token_id;
+use crate::commands::search::{StatusFilter, compare_by, market_liquidity, market_volume, parse_duration_secs};
+
+/// Markets fetched per Gamma page.
+const MARKETS_PAGE_SIZE: usize = 500;
+/// Token IDs per CLOB `/spreads` and `/midpoints` request.
+const PRICES_BATCH_SIZE: usize = 100;
+
+pub async fn execute(filters: Vec<String>, sort: Option<String>, max_markets: usize, limit: usize) -> Result<()> {
+    let conditions = filters.iter().map(|f| parse_condition(f)).collect::<Result<Vec<_>>>()?;
+    let sort = sort.as_deref().map(parse_sort).transpose()?;
+
+    let now = Utc::now();
+    let markets = fetch_active_markets(&conditions, now, max_markets).await?;
+    let tokens: Vec<(usize, String)> = markets
+        .iter()
+        .enumerate()
+        .filter(|(_, m)| m.enable_order_book.unwrap_or(false))
+        .filter_map(|(i, m)| yes_token_id(m).map(|t| (i, t)))
+        .collect();
+    let token_ids: Vec<String> = tokens.iter().map(|(_, t)| t.clone()).collect();
+    let (spreads, midpoints) = fetch_spreads_and_midpoints(&token_ids).await?;
+
+    let mut rows: Vec<(&Market, Metrics)> = markets.iter().map(|m| (m, Metrics::from_market(m, now))).collect();
+    for (i, token_id) in &tokens {
+        rows[*i].1.spread = spreads.get(token_id).copied().or(rows[*i].1.spread);
+        rows[*i].1.mid = midpoints.get(token_id).copied();
+    }
+    rows.retain(|(_, metrics)| conditions.iter().all(|c| c.matches(metrics)));
+    if let Some((field, descending)) = sort {
+        rows.sort_by(|a, b| compare_by(a.1.get(field), b.1.get(field), descending));
+    }
+
+    println!("markets = {}, matches = {}", markets.len(), rows.len());
+    for (market, metrics) in rows.iter().take(limit) {
+        let slug = market.slug.as_deref().unwrap_or(&market.id);
+        println!("- {} ({})", market.question.as_deref().unwrap_or_default(), slug);
+        println!(
+            "  mid = {}, spread = {}, volume24h = {}, volume = {}, liquidity = {}, ends = {}",
+            display(metrics.mid, 4),
+            display(metrics.spread, 4),
+            display(metrics.volume_24h, 0),
+            display(metrics.volume, 0),
+            display(metrics.liquidity, 0),
+            market.end_date.map(|d| d.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_else(|| "-".to_string())
+        );
+    }
+
+    Ok(())
+}
+
+/// Open markets from Gamma, busiest first. Bounds that Gamma can apply itself are passed
+/// along to cut down the paging.
+async fn fetch_active_markets(
+    conditions: &[Condition],
+    now: DateTime<Utc>,
+    max_markets: usize,
+) -> Result<Vec<Market>> {
+    let bound = |field: Field, ops: &[Op]| {
+        conditions.iter().filter(|c| c.field == field && ops.contains(&c.op)).map(|c| c.value).next()
+    };
+    let at = |secs: Decimal| {
+        let secs = i64::try_from(secs.trunc()).ok()?;
+        DateTime::from_timestamp(now.timestamp().checked_add(secs)?, 0)
+    };
+
+    let client = GammaClient::default();
+    let mut markets = Vec::new();
+    let mut offset = 0;
+    while markets.len() < max_markets {
+        let request = MarketsRequest::builder()
+            .closed(false)
+            .order("volume24hr".to_string())
+            .ascending(false)
+            .limit(MARKETS_PAGE_SIZE as i32)
+            .offset(offset as i32)
+            .maybe_liquidity_num_min(bound(Field::Liquidity, &[Op::Gt, Op::Ge]))
+            .maybe_volume_num_min(bound(Field::Volume, &[Op::Gt, Op::Ge]))
+            .maybe_end_date_max(bound(Field::Ends, &[Op::Lt, Op::Le]).and_then(at))
+            .maybe_end_date_min(bound(Field::Ends, &[Op::Gt, Op::Ge]).and_then(at))
+            .build();
+        let page = client.markets(&request).await.context("Failed to fetch markets")?;
+        let page_len = page.len();
+        markets.extend(page.into_iter().filter(|m| StatusFilter::Active.matches(m.active, m.closed, m.archived)));
+        if page_len < MARKETS_PAGE_SIZE {
+            break;
+        }
+        offset += page_len;
+    }
+    markets.truncate(max_markets);
+    Ok(markets)
+}
+
+/// Spreads and midpoints from the CLOB in batches. A failed batch is logged and its
+/// tokens left without prices.
+async fn fetch_spreads_and_midpoints(
+    token_ids: &[String],
+) -> Result<(HashMap<String, Decimal>, HashMap<String, Decimal>)> {
+    let client = ClobClient::new("https://clob.polymarket.com", ClobConfig::default())?;
+    let mut spreads = HashMap::new();
+    let mut midpoints = HashMap::new();
+    for chunk in token_ids.chunks(PRICES_BATCH_SIZE) {
+        let spread_requests: Vec<SpreadRequest> =
+            chunk.iter().map(|id| SpreadRequest::builder().token_id(id.clone()).build()).collect();
+        let midpoint_requests: Vec<MidpointRequest> =
+            chunk.iter().map(|id| MidpointRequest::builder().token_id(id.clone()).build()).collect();
+        let (spread_result, midpoint_result) =
+            futures::future::join(client.spreads(&spread_requests), client.midpoints(&midpoint_requests)).await;
+        match spread_result {
+            Ok(response) => spreads.extend(response.spreads.unwrap_or_default()),
+            Err(e) => log::warn!("error = {:#}, failed to fetch spreads", e),
+        }
+        match midpoint_result {
+            Ok(response) => midpoints.extend(response.midpoints),
+            Err(e) => log::warn!("error = {:#}, failed to fetch midpoints", e),
+        }
+    }
+    Ok((spreads, midpoints))
+}
+
+/// A market property that can be filtered and sorted on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Spread,
+    Mid,
+    Volume24h,
+    Volume,
+    Liquidity,
+    /// Seconds until the market's end date.
+    Ends,
+}
+
+pub fn parse_field(value: &str) -> Result<Field> {
+    match value.to_lowercase().as_str() {
+        "spread" => Ok(Field::Spread),
+        "mid" | "midpoint" => Ok(Field::Mid),
+        "volume24h" => Ok(Field::Volume24h),
+        "volume" => Ok(Field::Volume),
+        "liquidity" => Ok(Field::Liquidity),
+        "ends" => Ok(Field::Ends),
+        _ => anyhow::bail!(
+            "Invalid field: {} (must be spread, mid, volume24h, volume, liquidity or ends)",
+            value
+        ),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+}
+
+/// One filter expression, such as `spread<0.02` or `ends<7d`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub field: Field,
+    pub op: Op,
+    /// Seconds for `ends`, a plain number for everything else.
+    pub value: Decimal,
+}
+
+impl Condition {
+    /// Markets missing the value never match.
+    pub fn matches(&self, metrics: &Metrics) -> bool {
+        let Some(value) = metrics.get(self.field) else {
+            return false;
+        };
+        match self.op {
+            Op::Lt => value < self.value,
+            Op::Le => value <= self.value,
+            Op::Gt => value > self.value,
+            Op::Ge => value >= self.value,
+            Op::Eq => value == self.value,
+        }
+    }
+}
+
+/// Parse `<field><op><value>` with op one of `<`, `<=`, `>`, `>=`, `=`. Values of `ends` are
+/// durations such as `12h`, `7d` or `2w`.
+pub fn parse_condition(expr: &str) -> Result<Condition> {
+    let expr = expr.trim();
+    let start = expr
+        .find(['<', '>', '='])
+        .with_context(|| format!("Invalid filter: {} (expected e.g. spread<0.02)", expr))?;
+    let (field, rest) = expr.split_at(start);
+    let (op, value) = if let Some(v) = rest.strip_prefix("<=") {
+        (Op::Le, v)
+    } else if let Some(v) = rest.strip_prefix(">=") {
+        (Op::Ge, v)
+    } else if let Some(v) = rest.strip_prefix('<') {
+        (Op::Lt, v)
+    } else if let Some(v) = rest.strip_prefix('>') {
+        (Op::Gt, v)
+    } else {
+        (Op::Eq, &rest[1..])
+    };
+    let field = parse_field(field.trim())?;
+    let value = value.trim();
+    let value = if field == Field::Ends {
+        parse_duration_secs(value)
+            .map(Decimal::from)
+            .with_context(|| format!("Invalid duration in {}: use e.g. 12h, 7d or 2w", expr))?
+    } else {
+        Decimal::from_str(value).with_context(|| format!("Invalid number in {}", expr))?
+    };
+    Ok(Condition { field, op, value })
+}
+
+/// Parse a sort key: a field, optionally followed by `:asc` (default) or `:desc`.
+pub fn parse_sort(value: &str) -> Result<(Field, bool)> {
+    let (field, direction) = value.split_once(':').unwrap_or((value, "asc"));
+    let descending = match direction.to_lowercase().as_str() {
+        "asc" => false,
+        "desc" => true,
+        _ => anyhow::bail!("Invalid sort direction: {} (must be asc or desc)", direction),
+    };
+    Ok((parse_field(field)?, descending))
+}
+
+/// Values of one market for every field; spread and mid come from the CLOB.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metrics {
+    pub spread: Option<Decimal>,
+    pub mid: Option<Decimal>,
+    pub volume_24h: Option<Decimal>,
+    pub volume: Option<Decimal>,
+    pub liquidity: Option<Decimal>,
+    pub ends_in_secs: Option<Decimal>,
+}
+
+impl Metrics {
+    /// Everything Gamma knows; the spread is Gamma's until the CLOB's replaces it.
+    pub fn from_market(market: &Market, now: DateTime<Utc>) -> Self {
+        Metrics {
+            spread: market.spread,
+            mid: None,
+            volume_24h: market.volume_24hr,
+            volume: market_volume(market),
+            liquidity: market_liquidity(market),
+            ends_in_secs: market.end_date.map(|end| Decimal::from((end - now).num_seconds())),
+        }
+    }
+
+    pub fn get(&self, field: Field) -> Option<Decimal> {
+        match field {
+            Field::Spread => self.spread,
+            Field::Mid => self.mid,
+            Field::Volume24h => self.volume_24h,
+            Field::Volume => self.volume,
+            Field::Liquidity => self.liquidity,
+            Field::Ends => self.ends_in_secs,
+        }
+    }
+}
+
+fn display(value: Option<Decimal>, dp: u32) -> String {
+    value.map(|v| v.round_dp(dp).normalize().to_string()).unwrap_or_else(|| "-".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn test_parse_condition() {
+        assert_eq!(
+            parse_condition("spread<0.02").unwrap(),
+            Condition { field: Field::Spread, op: Op::Lt, value: dec("0.02") }
+        );
+        assert_eq!(
+            parse_condition("volume24h>=10000").unwrap(),
+            Condition { field: Field::Volume24h, op: Op::Ge, value: dec("10000") }
+        );
+        assert_eq!(
+            parse_condition("ends<7d").unwrap(),
+            Condition { field: Field::Ends, op: Op::Lt, value: Decimal::from(7 * 86_400) }
+        );
+        assert_eq!(parse_condition("mid=0.5").unwrap().op, Op::Eq);
+    }
+
+    #[test]
+    fn test_parse_condition_errors() {
+        assert!(parse_condition("spread").is_err());
+        assert!(parse_condition("price<0.5").is_err());
+        assert!(parse_condition("spread<abc").is_err());
+        assert!(parse_condition("ends<7").is_err());
+    }
+
+    #[test]
+    fn test_parse_sort() {
+        assert_eq!(parse_sort("spread").unwrap(), (Field::Spread, false));
+        assert_eq!(parse_sort("volume24h:desc").unwrap(), (Field::Volume24h, true));
+        assert!(parse_sort("volume24h:up").is_err());
+    }
+
+    #[test]
+    fn test_conditions_match_metrics() {
+        let metrics = Metrics {
+            spread: Some(dec("0.01")),
+            volume_24h: Some(dec("25000")),
+            ends_in_secs: Some(Decimal::from(3 * 86_400)),
+            ..Default::default()
+        };
+        for expr in ["spread<0.02", "volume24h>10000", "ends<7d"] {
+            assert!(parse_condition(expr).unwrap().matches(&metrics), "{}", expr);
+        }
+        assert!(!parse_condition("spread>0.01").unwrap().matches(&metrics));
+        // No midpoint, so no mid filter can match
+        assert!(!parse_condition("mid>0").unwrap().matches(&metrics));
+    }
+
+    #[test]
+    fn test_metrics_from_market() {
+        let now = DateTime::parse_from_rfc3339("2024-06-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        let market: Market = serde_json::from_value(serde_json::json!({
+            "id": "1",
+            "spread": 0.03,
+            "volume24hr": 1500,
+            "liquidity": "800",
+            "endDate": "2024-06-02T00:00:00Z"
+        }))
+        .unwrap();
+        let metrics = Metrics::from_market(&market, now);
+        assert_eq!(metrics.spread, Some(dec("0.03")));
+        assert_eq!(metrics.liquidity, Some(dec("800")));
+        assert_eq!(metrics.ends_in_secs, Some(Decimal::from(86_400)));
+        assert_eq!(metrics.mid, None);
+    }
+}
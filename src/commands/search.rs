@@ -1,50 +1,344 @@
 use anyhow::{Context, Result};
-use polymarket_client_sdk::gamma::{
-    Client as GammaClient,
-    types::request::SearchRequest,
+use polymarket_client_sdk::{
+    gamma::{
+        Client as GammaClient,
+        types::{
+            request::SearchRequest,
+            response::{Event, Market},
+        },
+    },
+    types::{DateTime, Decimal, NaiveDate, Utc},
 };
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+#[allow(clippy::too_many_arguments)]
+pub async fn execute(
+    query: String,
+    status: Option<String>,
+    tag: Option<String>,
+    min_liquidity: Option<Decimal>,
+    min_volume: Option<Decimal>,
+    ends_before: Option<String>,
+    ends_after: Option<String>,
+    sort: Option<String>,
+    limit: Option<i32>,
+    page: i32,
+    compact: bool,
+) -> Result<()> {
+    let now = Utc::now();
+    let filter = MarketFilter {
+        status: status.as_deref().map(parse_status).transpose()?,
+        min_liquidity,
+        min_volume,
+        ends_before: ends_before.as_deref().map(|v| parse_date_bound(v, now)).transpose()?,
+        ends_after: ends_after.as_deref().map(|v| parse_date_bound(v, now)).transpose()?,
+    };
+    let sort = sort.as_deref().map(parse_sort).transpose()?;
+    anyhow::ensure!(page >= 1, "--page starts at 1");
 
-pub async fn execute(query: String) -> Result<()> {
     let client = GammaClient::default();
-    let search = SearchRequest::builder().q(query).build();
+    let search = SearchRequest::builder()
+        .q(query)
+        .maybe_events_status(filter.status.and_then(StatusFilter::events_status).map(String::from))
+        .maybe_keep_closed_markets(filter.status.filter(|s| *s != StatusFilter::Active).map(|_| 1))
+        .events_tag(tag.iter().cloned().collect())
+        .maybe_sort(sort.map(SortKey::search_field).map(String::from))
+        .maybe_ascending(sort.map(|key| key == SortKey::EndDate))
+        .maybe_limit_per_type(limit)
+        .page(page)
+        .build();
     let results = client.search(&search).await.context("Failed to search markets")?;
 
-    if let Some(events) = results.events {
+    let mut events: Vec<Event> = results
+        .events
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|event| tag.as_deref().is_none_or(|tag| event_has_tag(event, tag)))
+        .filter_map(|mut event| {
+            let markets: Vec<Market> = event.markets.take()?.into_iter().filter(|m| filter.matches(m)).collect();
+            (!markets.is_empty()).then(|| {
+                event.markets = Some(markets);
+                event
+            })
+        })
+        .collect();
+
+    let matched = if compact {
+        events.iter().map(|e| e.markets.as_ref().map_or(0, Vec::len)).sum()
+    } else {
+        events.len()
+    };
+
+    if events.is_empty() {
+        println!("No events found on page {}.", page);
+    } else if compact {
+        let mut markets: Vec<Market> = events.into_iter().flat_map(|e| e.markets.unwrap_or_default()).collect();
+        if let Some(key) = sort {
+            markets.sort_by(|a, b| compare_markets(a, b, key));
+        }
+        println!("Found {} markets:", markets.len());
+        for market in &markets {
+            println!("{}", compact_line(market));
+        }
+    } else {
+        if let Some(key) = sort {
+            for event in &mut events {
+                if let Some(markets) = event.markets.as_mut() {
+                    markets.sort_by(|a, b| compare_markets(a, b, key));
+                }
+            }
+            events.sort_by(|a, b| compare_events(a, b, key));
+        }
         println!("Found {} events:", events.len());
         for event in events {
             println!("Event: {} (ID: {})", event.title.unwrap_or_default(), event.id);
-            if let Some(markets) = event.markets {
-                for market in markets {
-                    println!("  - Market: {} (ID: {})", market.question.unwrap_or_default(), market.id);
-
-                    let outcomes_str = market.outcomes.unwrap_or_else(|| "[]".to_string());
-                    let token_ids_str = market.clob_token_ids.unwrap_or_else(|| "[]".to_string());
+            for market in event.markets.unwrap_or_default() {
+                println!("  - Market: {} (ID: {})", market.question.as_deref().unwrap_or_default(), market.id);
+                println!(
+                    "    volume = {}, liquidity = {}, ends = {}",
+                    display_amount(market_volume(&market)),
+                    display_amount(market_liquidity(&market)),
+                    display_date(market.end_date)
+                );
 
-                    let outcomes_list: Vec<String> = serde_json::from_str(&outcomes_str).unwrap_or_default();
-                    let token_ids_list: Vec<String> = serde_json::from_str(&token_ids_str).unwrap_or_default();
+                let outcomes_str = market.outcomes.unwrap_or_else(|| "[]".to_string());
+                let token_ids_str = market.clob_token_ids.unwrap_or_else(|| "[]".to_string());
 
-                    if !outcomes_list.is_empty() && outcomes_list.len() == token_ids_list.len() {
+                match parse_outcomes(&outcomes_str, &token_ids_str) {
+                    Some(outcomes) => {
                         println!("    Outcomes:");
-                        for (outcome, token_id) in outcomes_list.iter().zip(token_ids_list.iter()) {
+                        for (outcome, token_id) in outcomes {
                             println!("      - {}: {}", outcome, token_id);
                         }
-                    } else {
+                    }
+                    None => {
                         println!("    Outcomes (raw): {}", outcomes_str);
                         println!("    Token IDs (raw): {}", token_ids_str);
                     }
                 }
             }
         }
-    } else {
-        println!("No events found.");
+    }
+
+    if let Some(pagination) = results.pagination {
+        let has_more = pagination.has_more.unwrap_or(false);
+        println!(
+            "page = {}, has_more = {}, total = {}, matched = {}",
+            page,
+            has_more,
+            pagination.total_results.map(|t| t.to_string()).unwrap_or_else(|| "-".to_string()),
+            matched
+        );
+        if has_more && filter.is_client_side() {
+            println!("Liquidity, volume and end-date filters only apply to this page; later pages may have more matches.");
+        }
     }
 
     Ok(())
 }
 
+/// Which markets to keep, by their lifecycle flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusFilter {
+    Active,
+    Closed,
+    Archived,
+    All,
+}
+
+impl StatusFilter {
+    pub fn matches(self, active: Option<bool>, closed: Option<bool>, archived: Option<bool>) -> bool {
+        let (active, closed, archived) = (active.unwrap_or(false), closed.unwrap_or(false), archived.unwrap_or(false));
+        match self {
+            StatusFilter::Active => active && !closed && !archived,
+            StatusFilter::Closed => closed && !archived,
+            StatusFilter::Archived => archived,
+            StatusFilter::All => true,
+        }
+    }
+
+    /// The `events_status` value Gamma's search understands, if any.
+    fn events_status(self) -> Option<&'static str> {
+        match self {
+            StatusFilter::Active => Some("active"),
+            StatusFilter::Closed | StatusFilter::Archived => Some("closed"),
+            StatusFilter::All => None,
+        }
+    }
+}
+
+pub fn parse_status(value: &str) -> Result<StatusFilter> {
+    match value.to_lowercase().as_str() {
+        "active" => Ok(StatusFilter::Active),
+        "closed" => Ok(StatusFilter::Closed),
+        "archived" => Ok(StatusFilter::Archived),
+        "all" => Ok(StatusFilter::All),
+        _ => anyhow::bail!("Invalid status: must be 'active', 'closed', 'archived' or 'all'"),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Volume,
+    Liquidity,
+    EndDate,
+}
+
+impl SortKey {
+    /// The event field Gamma's search sorts by, so the order holds across pages.
+    fn search_field(self) -> &'static str {
+        match self {
+            SortKey::Volume => "volume",
+            SortKey::Liquidity => "liquidity",
+            SortKey::EndDate => "endDate",
+        }
+    }
+}
+
+pub fn parse_sort(value: &str) -> Result<SortKey> {
+    match value.to_lowercase().as_str() {
+        "volume" => Ok(SortKey::Volume),
+        "liquidity" => Ok(SortKey::Liquidity),
+        "end-date" | "end_date" | "ends" => Ok(SortKey::EndDate),
+        _ => anyhow::bail!("Invalid sort: must be 'volume', 'liquidity' or 'end-date'"),
+    }
+}
+
+/// Market-level filters. A market missing a value that a filter needs doesn't match.
+#[derive(Debug, Clone, Default)]
+pub struct MarketFilter {
+    pub status: Option<StatusFilter>,
+    pub min_liquidity: Option<Decimal>,
+    pub min_volume: Option<Decimal>,
+    pub ends_before: Option<DateTime<Utc>>,
+    pub ends_after: Option<DateTime<Utc>>,
+}
+
+impl MarketFilter {
+    /// Whether any filter is applied here rather than by Gamma, and so only to the fetched page.
+    pub fn is_client_side(&self) -> bool {
+        self.min_liquidity.is_some() || self.min_volume.is_some() || self.ends_before.is_some() || self.ends_after.is_some()
+    }
+
+    pub fn matches(&self, market: &Market) -> bool {
+        let at_least = |value: Option<Decimal>, min: Option<Decimal>| match min {
+            Some(min) => value.is_some_and(|v| v >= min),
+            None => true,
+        };
+        self.status.is_none_or(|s| s.matches(market.active, market.closed, market.archived))
+            && at_least(market_liquidity(market), self.min_liquidity)
+            && at_least(market_volume(market), self.min_volume)
+            && self.ends_before.is_none_or(|t| market.end_date.is_some_and(|end| end < t))
+            && self.ends_after.is_none_or(|t| market.end_date.is_some_and(|end| end > t))
+    }
+}
+
+/// Whether the event carries the tag, matched case-insensitively against tag slugs and
+/// labels and the event category. Events without any tag data are kept, since Gamma
+/// already filtered them by tag.
+pub fn event_has_tag(event: &Event, tag: &str) -> bool {
+    let tags = event.tags.as_deref().unwrap_or_default();
+    if tags.is_empty() && event.category.is_none() {
+        return true;
+    }
+    let eq = |value: Option<&str>| value.is_some_and(|v| v.eq_ignore_ascii_case(tag));
+    tags.iter().any(|t| eq(t.slug.as_deref()) || eq(t.label.as_deref())) || eq(event.category.as_deref())
+}
+
+/// Parse a date bound: `YYYY-MM-DD` (midnight UTC), RFC 3339, or a duration from now such
+/// as `12h`, `7d` or `2w`.
+pub fn parse_date_bound(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let value = value.trim();
+    if let Some(seconds) = parse_duration_secs(value) {
+        return DateTime::from_timestamp(now.timestamp() + seconds, 0).context("Date out of range");
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).context("Invalid date")?.and_utc());
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|d| d.with_timezone(&Utc))
+        .with_context(|| format!("Invalid date: {} (use YYYY-MM-DD, RFC 3339 or a duration like 7d)", value))
+}
+
+/// Parse `<n>m`, `<n>h`, `<n>d` or `<n>w` into seconds.
+pub fn parse_duration_secs(value: &str) -> Option<i64> {
+    let unit = match value.chars().last()? {
+        'm' => 60,
+        'h' => 3_600,
+        'd' => 86_400,
+        'w' => 604_800,
+        _ => return None,
+    };
+    let count: i64 = value[..value.len() - 1].parse().ok()?;
+    count.checked_mul(unit)
+}
+
+pub fn market_volume(market: &Market) -> Option<Decimal> {
+    market.volume_num.or_else(|| market.volume.as_deref().and_then(|v| Decimal::from_str(v).ok()))
+}
+
+pub fn market_liquidity(market: &Market) -> Option<Decimal> {
+    market.liquidity_num.or_else(|| market.liquidity.as_deref().and_then(|v| Decimal::from_str(v).ok()))
+}
+
+/// Volume and liquidity sort largest first, end dates soonest first; missing values go last.
+pub fn compare_by<T: Ord>(a: Option<T>, b: Option<T>, descending: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if descending => b.cmp(&a),
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+pub fn compare_markets(a: &Market, b: &Market, key: SortKey) -> Ordering {
+    match key {
+        SortKey::Volume => compare_by(market_volume(a), market_volume(b), true),
+        SortKey::Liquidity => compare_by(market_liquidity(a), market_liquidity(b), true),
+        SortKey::EndDate => compare_by(a.end_date, b.end_date, false),
+    }
+}
+
+fn compare_events(a: &Event, b: &Event, key: SortKey) -> Ordering {
+    match key {
+        SortKey::Volume => compare_by(a.volume, b.volume, true),
+        SortKey::Liquidity => compare_by(a.liquidity, b.liquidity, true),
+        SortKey::EndDate => compare_by(a.end_date, b.end_date, false),
+    }
+}
+
+/// One line per market: ID, question, volume, liquidity, end date and outcome prices.
+pub fn compact_line(market: &Market) -> String {
+    let outcomes: Vec<String> = serde_json::from_str(market.outcomes.as_deref().unwrap_or("[]")).unwrap_or_default();
+    let prices: Vec<String> =
+        serde_json::from_str(market.outcome_prices.as_deref().unwrap_or("[]")).unwrap_or_default();
+    let mut line = format!(
+        "{} | {} | volume = {}, liquidity = {}, ends = {}",
+        market.id,
+        market.question.as_deref().unwrap_or_default(),
+        display_amount(market_volume(market)),
+        display_amount(market_liquidity(market)),
+        display_date(market.end_date)
+    );
+    if outcomes.len() == prices.len() && !outcomes.is_empty() {
+        let pairs: Vec<String> = outcomes.iter().zip(&prices).map(|(o, p)| format!("{} {}", o, p)).collect();
+        line.push_str(&format!(" | {}", pairs.join(" / ")));
+    }
+    line
+}
+
+fn display_amount(value: Option<Decimal>) -> String {
+    value.map(|v| v.round_dp(0).to_string()).unwrap_or_else(|| "-".to_string())
+}
+
+fn display_date(value: Option<DateTime<Utc>>) -> String {
+    value.map(|d| d.format("%Y-%m-%d").to_string()).unwrap_or_else(|| "-".to_string())
+}
+
 /// Parse outcomes and token IDs from their JSON string representations.
 /// Returns paired (outcome, token_id) tuples if both lists parse and have equal length.
-#[allow(dead_code)]
 pub fn parse_outcomes(outcomes_str: &str, token_ids_str: &str) -> Option<Vec<(String, String)>> {
     let outcomes: Vec<String> = serde_json::from_str(outcomes_str).ok()?;
     let token_ids: Vec<String> = serde_json::from_str(token_ids_str).ok()?;
@@ -53,7 +347,7 @@ pub fn parse_outcomes(outcomes_str: &str, token_ids_str: &str) -> Option<Vec<(St
         return None;
     }
 
-    Some(outcomes.into_iter().zip(token_ids.into_iter()).collect())
+    Some(outcomes.into_iter().zip(token_ids).collect())
 }
 
 #[cfg(test)]
@@ -106,4 +400,97 @@ mod tests {
         assert_eq!(pairs.len(), 1);
         assert_eq!(pairs[0], ("Yes".to_string(), "token_x".to_string()));
     }
+
+    fn market(value: serde_json::Value) -> Market {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2024-06-01T00:00:00Z").unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_status_filter() {
+        assert!(StatusFilter::Active.matches(Some(true), Some(false), None));
+        assert!(!StatusFilter::Active.matches(Some(true), Some(true), None));
+        assert!(StatusFilter::Closed.matches(Some(true), Some(true), Some(false)));
+        assert!(!StatusFilter::Closed.matches(None, Some(true), Some(true)));
+        assert!(StatusFilter::Archived.matches(None, Some(true), Some(true)));
+        assert!(StatusFilter::All.matches(None, None, None));
+        assert!(parse_status("bogus").is_err());
+    }
+
+    #[test]
+    fn test_parse_date_bound() {
+        assert_eq!(parse_date_bound("7d", now()).unwrap().to_rfc3339(), "2024-06-08T00:00:00+00:00");
+        assert_eq!(parse_date_bound("12h", now()).unwrap().to_rfc3339(), "2024-06-01T12:00:00+00:00");
+        assert_eq!(parse_date_bound("2024-12-31", now()).unwrap().to_rfc3339(), "2024-12-31T00:00:00+00:00");
+        assert_eq!(
+            parse_date_bound("2024-12-31T12:00:00-02:00", now()).unwrap().to_rfc3339(),
+            "2024-12-31T14:00:00+00:00"
+        );
+        assert!(parse_date_bound("next week", now()).is_err());
+    }
+
+    #[test]
+    fn test_market_filter() {
+        let m = market(serde_json::json!({
+            "id": "1",
+            "active": true,
+            "closed": false,
+            "volumeNum": 5000,
+            "liquidity": "1200.5",
+            "endDate": "2024-06-05T00:00:00Z"
+        }));
+        assert!(MarketFilter::default().matches(&m));
+        let filter = MarketFilter {
+            status: Some(StatusFilter::Active),
+            min_liquidity: Some(Decimal::from(1000)),
+            min_volume: Some(Decimal::from(5000)),
+            ends_before: Some(parse_date_bound("7d", now()).unwrap()),
+            ends_after: Some(now()),
+        };
+        assert!(filter.matches(&m));
+        assert!(!MarketFilter { min_volume: Some(Decimal::from(5001)), ..Default::default() }.matches(&m));
+        assert!(!MarketFilter { status: Some(StatusFilter::Closed), ..Default::default() }.matches(&m));
+        assert!(!MarketFilter { ends_before: Some(now()), ..Default::default() }.matches(&m));
+        // Missing values don't pass a minimum
+        let bare = market(serde_json::json!({ "id": "2" }));
+        assert!(!MarketFilter { min_liquidity: Some(Decimal::ONE), ..Default::default() }.matches(&bare));
+    }
+
+    #[test]
+    fn test_market_filter_is_client_side() {
+        assert!(!MarketFilter { status: Some(StatusFilter::Active), ..Default::default() }.is_client_side());
+        assert!(MarketFilter { min_volume: Some(Decimal::ONE), ..Default::default() }.is_client_side());
+        assert!(MarketFilter { ends_after: Some(now()), ..Default::default() }.is_client_side());
+    }
+
+    #[test]
+    fn test_compare_markets() {
+        let mut markets = [
+            market(serde_json::json!({ "id": "a", "volumeNum": 10, "endDate": "2024-07-01T00:00:00Z" })),
+            market(serde_json::json!({ "id": "b" })),
+            market(serde_json::json!({ "id": "c", "volumeNum": 50, "endDate": "2024-06-10T00:00:00Z" })),
+        ];
+        markets.sort_by(|a, b| compare_markets(a, b, SortKey::Volume));
+        assert_eq!(markets.iter().map(|m| m.id.as_str()).collect::<Vec<_>>(), ["c", "a", "b"]);
+        markets.sort_by(|a, b| compare_markets(a, b, SortKey::EndDate));
+        assert_eq!(markets.iter().map(|m| m.id.as_str()).collect::<Vec<_>>(), ["c", "a", "b"]);
+    }
+
+    #[test]
+    fn test_compact_line() {
+        let m = market(serde_json::json!({
+            "id": "42",
+            "question": "Will it rain?",
+            "volumeNum": 1234.6,
+            "outcomes": "[\"Yes\",\"No\"]",
+            "outcomePrices": "[\"0.3\",\"0.7\"]"
+        }));
+        assert_eq!(
+            compact_line(&m),
+            "42 | Will it rain? | volume = 1235, liquidity = -, ends = - | Yes 0.3 / No 0.7"
+        );
+    }
 }
@@ -0,0 +1,257 @@
+use anyhow::{Context, Result};
+use alloy::primitives::{B256, Bytes, U256};
+use alloy::providers::{Provider, ProviderBuilder};
+use alloy::sol_types::SolCall;
+use polymarket_client_sdk::{
+    POLYGON, PRIVATE_KEY_VAR,
+    auth::{LocalSigner, Signer},
+    clob::{Client as ClobClient, Config as ClobConfig},
+    contract_config,
+    types::Address,
+};
+use std::env;
+use std::str::FromStr;
+
+use crate::commands::status::{format_balance, parse_balance};
+use crate::constants::USDC_E_ADDRESS;
+use crate::contracts::{
+    IConditionalTokens, IERC20, IERC1155, INegRiskAdapter,
+    call3, aggregate3, decode_result,
+};
+use crate::rpc;
+use crate::wallet::{funder_address, send_call, signature_type_from_env};
+
+pub async fn execute(market: String, amount: String, dry_run: bool, rpc_urls: &[String]) -> Result<()> {
+    let amount = parse_balance(&amount)?;
+
+    let private_key = env::var(PRIVATE_KEY_VAR).context("Need PRIVATE_KEY environment variable")?;
+    let signer = LocalSigner::from_str(&private_key)?.with_chain_id(Some(POLYGON));
+    let owner = signer.address();
+    let signature_type = signature_type_from_env()?;
+    let holder = funder_address(owner, signature_type)?;
+    println!("wallet = {}, holder = {}, signature_type = {}", owner, holder, signature_type);
+
+    let market = fetch_binary_market(&market).await?;
+    println!("market = {}, condition = {}, neg_risk = {}", market.question, market.condition_id, market.neg_risk);
+
+    let provider = ProviderBuilder::new()
+        .wallet(signer.clone())
+        .connect_client(rpc::connect(rpc_urls).await?);
+
+    let contracts = SetContracts::polygon()?;
+    let spender = contracts.operator(market.neg_risk);
+
+    let state = read_set_state(&provider, &contracts, holder, spender, &market).await?;
+    println!(
+        "usdc = ${}, yes = {}, no = {}",
+        format_balance(state.collateral),
+        format_balance(state.balances[0]),
+        format_balance(state.balances[1])
+    );
+    anyhow::ensure!(
+        state.collateral >= amount,
+        "Not enough USDC.e in {}: have ${}, need ${}",
+        holder,
+        format_balance(state.collateral),
+        format_balance(amount)
+    );
+
+    if state.allowance < amount {
+        let data: Bytes = IERC20::approveCall { spender, value: U256::MAX }.abi_encode().into();
+        if dry_run {
+            println!("allowance = ${}, would approve {} for USDC.e", format_balance(state.allowance), spender);
+        } else {
+            let tx_hash = send_call(&provider, owner, signature_type, USDC_E_ADDRESS, data).await?;
+            println!("tx = {}, approved {} for USDC.e", tx_hash, spender);
+        }
+    }
+
+    let data = split_calldata(market.condition_id, market.neg_risk, amount);
+    if dry_run {
+        println!(
+            "would split ${} into {} yes + {} no via {}",
+            format_balance(amount),
+            format_balance(amount),
+            format_balance(amount),
+            contracts.label(market.neg_risk)
+        );
+        return Ok(());
+    }
+
+    let tx_hash = send_call(&provider, owner, signature_type, spender, data).await?;
+    println!("tx = {}, split", tx_hash);
+
+    let after = read_set_state(&provider, &contracts, holder, spender, &market).await?;
+    println!(
+        "usdc = ${}, yes = {}, no = {}",
+        format_balance(after.collateral),
+        format_balance(after.balances[0]),
+        format_balance(after.balances[1])
+    );
+
+    Ok(())
+}
+
+/// A binary market as needed for complete-set operations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinaryMarket {
+    pub condition_id: B256,
+    pub question: String,
+    pub neg_risk: bool,
+    /// Position IDs of the first (index set 1) and second (index set 2) outcome tokens.
+    pub token_ids: [U256; 2],
+}
+
+/// Look up a market's outcome tokens and neg-risk flag from the CLOB.
+pub async fn fetch_binary_market(condition_id: &str) -> Result<BinaryMarket> {
+    B256::from_str(condition_id).context("Invalid condition ID")?;
+    let client = ClobClient::new("https://clob.polymarket.com", ClobConfig::default())?;
+    let market = client.market(condition_id).await.context("Failed to fetch market")?;
+    let token_ids: Vec<String> = market.tokens.iter().map(|t| t.token_id.clone()).collect();
+    binary_market(&market.condition_id, &market.question, market.neg_risk, &token_ids)
+}
+
+/// Build a market entry from CLOB fields, checking it has exactly two outcome tokens.
+pub fn binary_market(condition_id: &str, question: &str, neg_risk: bool, token_ids: &[String]) -> Result<BinaryMarket> {
+    let condition_id = B256::from_str(condition_id).context("Invalid condition ID")?;
+    let [yes, no] = token_ids else {
+        anyhow::bail!("Market {} has {} outcome tokens, expected 2", condition_id, token_ids.len());
+    };
+    Ok(BinaryMarket {
+        condition_id,
+        question: question.to_string(),
+        neg_risk,
+        token_ids: [
+            U256::from_str(yes).context("Invalid token ID")?,
+            U256::from_str(no).context("Invalid token ID")?,
+        ],
+    })
+}
+
+/// Contracts that split and merge positions: Conditional Tokens for standard markets,
+/// the Neg Risk Adapter for neg-risk ones.
+pub struct SetContracts {
+    pub conditional_tokens: Address,
+    pub neg_risk_adapter: Address,
+}
+
+impl SetContracts {
+    pub fn polygon() -> Result<Self> {
+        let config = contract_config(POLYGON, false).context("Failed to get contract config")?;
+        let neg_risk_config = contract_config(POLYGON, true).context("Failed to get neg risk contract config")?;
+        Ok(Self {
+            conditional_tokens: config.conditional_tokens,
+            neg_risk_adapter: neg_risk_config.neg_risk_adapter.context("No Neg Risk Adapter configured")?,
+        })
+    }
+
+    /// The contract to call for a market.
+    pub fn operator(&self, neg_risk: bool) -> Address {
+        if neg_risk { self.neg_risk_adapter } else { self.conditional_tokens }
+    }
+
+    pub fn label(&self, neg_risk: bool) -> &'static str {
+        if neg_risk { "Neg Risk Adapter" } else { "Conditional Tokens" }
+    }
+}
+
+/// Holdings and approvals relevant to splitting or merging one market.
+pub struct SetState {
+    /// USDC.e balance.
+    pub collateral: U256,
+    /// USDC.e allowance granted to the operator.
+    pub allowance: U256,
+    /// Whether the operator may move the holder's outcome tokens.
+    pub approved_for_all: bool,
+    pub balances: [U256; 2],
+}
+
+/// Read collateral, approvals and outcome token balances in one Multicall3 round-trip.
+pub async fn read_set_state<P: Provider>(
+    provider: &P,
+    contracts: &SetContracts,
+    holder: Address,
+    operator: Address,
+    market: &BinaryMarket,
+) -> Result<SetState> {
+    let ctf = contracts.conditional_tokens;
+    let calls = vec![
+        call3(USDC_E_ADDRESS, IERC20::balanceOfCall { account: holder }),
+        call3(USDC_E_ADDRESS, IERC20::allowanceCall { owner: holder, spender: operator }),
+        call3(ctf, IERC1155::isApprovedForAllCall { account: holder, operator }),
+        call3(ctf, IERC1155::balanceOfCall { account: holder, id: market.token_ids[0] }),
+        call3(ctf, IERC1155::balanceOfCall { account: holder, id: market.token_ids[1] }),
+    ];
+    let results = aggregate3(provider, calls).await?;
+    Ok(SetState {
+        collateral: decode_result::<IERC20::balanceOfCall>(&results[0]).context("Failed to read USDC.e balance")?,
+        allowance: decode_result::<IERC20::allowanceCall>(&results[1]).context("Failed to read USDC.e allowance")?,
+        approved_for_all: decode_result::<IERC1155::isApprovedForAllCall>(&results[2])
+            .context("Failed to read CTF approval")?,
+        balances: [
+            decode_result::<IERC1155::balanceOfCall>(&results[3]).context("Failed to read outcome token balance")?,
+            decode_result::<IERC1155::balanceOfCall>(&results[4]).context("Failed to read outcome token balance")?,
+        ],
+    })
+}
+
+/// Index sets of the two outcomes of a binary condition.
+pub fn binary_partition() -> Vec<U256> {
+    vec![U256::from(1), U256::from(2)]
+}
+
+/// Calldata splitting `amount` of USDC.e into one full set of outcome tokens per unit.
+pub fn split_calldata(condition_id: B256, neg_risk: bool, amount: U256) -> Bytes {
+    if neg_risk {
+        INegRiskAdapter::splitPositionCall { conditionId: condition_id, amount }.abi_encode().into()
+    } else {
+        IConditionalTokens::splitPositionCall {
+            collateralToken: USDC_E_ADDRESS,
+            parentCollectionId: B256::ZERO,
+            conditionId: condition_id,
+            partition: binary_partition(),
+            amount,
+        }
+        .abi_encode()
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONDITION: &str = "0xdd22472e552920b8438158ea7238bfadfa4f736aa4cee91a6b86c39ead110917";
+
+    #[test]
+    fn test_binary_market_orders_tokens() {
+        let market = binary_market(CONDITION, "Q", true, &["11".to_string(), "22".to_string()]).unwrap();
+        assert_eq!(market.token_ids, [U256::from(11), U256::from(22)]);
+        assert!(market.neg_risk);
+    }
+
+    #[test]
+    fn test_binary_market_rejects_non_binary() {
+        assert!(binary_market(CONDITION, "Q", false, &["11".to_string()]).is_err());
+        assert!(binary_market("0x12", "Q", false, &["11".to_string(), "22".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_split_calldata_ctf() {
+        let condition_id = B256::from_str(CONDITION).unwrap();
+        let data = split_calldata(condition_id, false, U256::from(5_000_000u64));
+        let decoded = IConditionalTokens::splitPositionCall::abi_decode(&data).unwrap();
+        assert_eq!(decoded.collateralToken, USDC_E_ADDRESS);
+        assert_eq!(decoded.partition, binary_partition());
+        assert_eq!(decoded.amount, U256::from(5_000_000u64));
+    }
+
+    #[test]
+    fn test_split_calldata_neg_risk() {
+        let condition_id = B256::from_str(CONDITION).unwrap();
+        let data = split_calldata(condition_id, true, U256::from(5_000_000u64));
+        let decoded = INegRiskAdapter::splitPositionCall::abi_decode(&data).unwrap();
+        assert_eq!(decoded.conditionId, condition_id);
+        assert_eq!(decoded.amount, U256::from(5_000_000u64));
+    }
+}
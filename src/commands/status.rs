@@ -1,18 +1,26 @@
 use anyhow::{Context, Result};
-use alloy::providers::ProviderBuilder;
+use alloy::primitives::U256;
+use alloy::providers::{Provider, ProviderBuilder};
 use polymarket_client_sdk::{
     POLYGON, PRIVATE_KEY_VAR,
     auth::{LocalSigner, Signer},
-    derive_proxy_wallet,
+    clob::{
+        Client as ClobClient, Config as ClobConfig,
+        types::{AssetType, SignatureType, request::BalanceAllowanceRequest},
+    },
+    contract_config, derive_proxy_wallet, derive_safe_wallet,
     types::{Address, Decimal},
 };
 use std::env;
 use std::str::FromStr;
 
-use crate::constants::{RPC_URL, USDC_E_ADDRESS, USDC_NATIVE_ADDRESS};
-use crate::contracts::{new_erc20, check_balance};
+use crate::commands::approve::{build_approval_targets, pending_approvals, read_approval_states};
+use crate::constants::{MULTICALL3_ADDRESS, USDC_E_ADDRESS, USDC_NATIVE_ADDRESS};
+use crate::contracts::{IERC20, IMulticall3, call3, aggregate3, decode_result};
+use crate::rpc;
+use crate::wallet::signature_type_from_env;
 
-pub async fn execute() -> Result<()> {
+pub async fn execute(rpc_urls: &[String]) -> Result<()> {
     let private_key = env::var(PRIVATE_KEY_VAR).context("Need PRIVATE_KEY environment variable")?;
     let signer = LocalSigner::from_str(&private_key)?.with_chain_id(Some(POLYGON));
     let owner = signer.address();
@@ -21,33 +29,268 @@ pub async fn execute() -> Result<()> {
     let proxy_address = derive_proxy_wallet(owner, POLYGON).context("Failed to derive proxy wallet")?;
     println!("Proxy Address: {}", proxy_address);
 
+    let safe_address = derive_safe_wallet(owner, POLYGON).context("Failed to derive safe wallet")?;
+    println!("Safe Address: {}", safe_address);
+
+    let signature_type = signature_type_from_env()?;
+    println!("Signature Type: {}", signature_type);
+
     let provider = ProviderBuilder::new()
         .wallet(signer.clone())
-        .connect(RPC_URL)
-        .await?;
+        .connect_client(rpc::connect(rpc_urls).await?);
 
-    let tokens = [
-        ("USDC.e", new_erc20(USDC_E_ADDRESS, provider.clone())),
-        ("USDC (Native)", new_erc20(USDC_NATIVE_ADDRESS, provider.clone())),
+    let wallets = [
+        ("EOA", owner),
+        ("Proxy", proxy_address),
+        ("Safe", safe_address),
     ];
+    let balances = fetch_balances(&provider, &wallets).await?;
+
+    for wallet in &balances {
+        println!("{} ({}):", wallet.label, wallet.address);
+        for (name, balance) in &wallet.tokens {
+            match balance {
+                Ok(balance) => println!("  {}: {}", name, display_usdc(*balance)),
+                Err(e) => println!("  {}: error ({})", name, e),
+            }
+        }
+        match &wallet.pol {
+            Ok(pol) => println!("  POL: {}", display_pol(*pol)),
+            Err(e) => println!("  POL: error ({})", e),
+        }
+    }
+
+    let client = ClobClient::new("https://clob.polymarket.com", ClobConfig::default())?
+        .authentication_builder(&signer)
+        .signature_type(signature_type)
+        .authenticate()
+        .await;
+    match client {
+        Ok(client) => {
+            let request = BalanceAllowanceRequest::builder().asset_type(AssetType::Collateral).build();
+            match client.balance_allowance(request).await {
+                Ok(response) => {
+                    println!("CLOB Collateral: ${}", response.balance / Decimal::from(1_000_000));
+                    for (spender, allowance) in &response.allowances {
+                        println!("  Allowance {}: {}", spender, allowance);
+                    }
+                }
+                Err(e) => println!("CLOB Collateral: error ({})", e),
+            }
+        }
+        Err(e) => println!("CLOB Collateral: error (failed to authenticate: {})", e),
+    }
 
-    for (name, token) in &tokens {
-        let balance = check_balance(token, proxy_address).await?;
-        let balance_fmt = format_balance(balance);
-        println!("{}: ${}", name, balance_fmt);
+    let mut warnings = collateral_warnings(signature_type, &balances);
+
+    let eoa_pol = balances.iter().find(|w| w.address == owner).and_then(|w| w.pol.as_ref().ok().copied());
+    if let Some(eoa_pol) = eoa_pol {
+        match approve_gas_warning(&provider, owner, eoa_pol).await {
+            Ok(warning) => warnings.extend(warning),
+            Err(e) => println!("Approval State: error ({:#})", e),
+        }
+    }
+
+    if !warnings.is_empty() {
+        println!("Warnings:");
+        for warning in &warnings {
+            println!("  - {}", warning);
+        }
     }
 
     Ok(())
 }
 
+/// Warn when the EOA can't pay the gas for the approvals `approve` would still send.
+async fn approve_gas_warning<P: Provider>(provider: &P, owner: Address, eoa_pol: U256) -> Result<Option<String>> {
+    let conditional_tokens = contract_config(POLYGON, false).context("Failed to get contract config")?.conditional_tokens;
+    let states = read_approval_states(provider, owner, conditional_tokens, &build_approval_targets(POLYGON)?).await?;
+    let approve_txs = pending_approvals(&states, conditional_tokens).len();
+    if approve_txs == 0 {
+        return Ok(None);
+    }
+    let gas_price = provider.get_gas_price().await.context("Failed to fetch gas price")?;
+    let required = U256::from(gas_price) * U256::from(APPROVE_GAS_PER_TX * approve_txs as u64);
+    Ok((eoa_pol < required).then(|| {
+        format!(
+            "EOA has {} POL, approve needs about {} POL for gas ({} transactions)",
+            display_pol(eoa_pol),
+            display_pol(required),
+            approve_txs
+        )
+    }))
+}
+
+/// Gas budgeted per approval transaction when checking whether the EOA can afford `approve`.
+pub const APPROVE_GAS_PER_TX: u64 = 60_000;
+
+/// Token and gas balances of one wallet.
+pub struct WalletBalances {
+    pub label: &'static str,
+    pub address: Address,
+    /// `(token name, balance)` for each token in `token_list`.
+    pub tokens: Vec<(&'static str, Result<U256>)>,
+    pub pol: Result<U256>,
+}
+
+impl WalletBalances {
+    /// USDC.e balance, the only token the CLOB accepts as collateral. Zero if the read failed.
+    pub fn collateral(&self) -> U256 {
+        self.token("USDC.e")
+    }
+
+    /// Balance of the named token. Zero if the read failed.
+    pub fn token(&self, name: &str) -> U256 {
+        self.tokens
+            .iter()
+            .find(|(n, _)| *n == name)
+            .and_then(|(_, b)| b.as_ref().ok().copied())
+            .unwrap_or_default()
+    }
+}
+
+/// Fetch every token in `token_list` plus the POL balance for each wallet, in one Multicall3 round-trip.
+pub async fn fetch_balances<P: Provider>(
+    provider: &P,
+    wallets: &[(&'static str, Address)],
+) -> Result<Vec<WalletBalances>> {
+    let tokens = token_list();
+
+    let mut calls = Vec::new();
+    for (_, account) in wallets {
+        for (_, token_address) in &tokens {
+            calls.push(call3(*token_address, IERC20::balanceOfCall { account: *account }));
+        }
+        calls.push(call3(MULTICALL3_ADDRESS, IMulticall3::getEthBalanceCall { addr: *account }));
+    }
+
+    let results = aggregate3(provider, calls).await?;
+    let mut results = results.iter();
+
+    let mut balances = Vec::new();
+    for (label, account) in wallets {
+        let mut token_balances = Vec::new();
+        for (name, _) in &tokens {
+            let result = results.next().context("Missing multicall result")?;
+            token_balances.push((*name, decode_result::<IERC20::balanceOfCall>(result)));
+        }
+        let pol = results.next().context("Missing multicall result")?;
+        balances.push(WalletBalances {
+            label,
+            address: *account,
+            tokens: token_balances,
+            pol: decode_result::<IMulticall3::getEthBalanceCall>(pol),
+        });
+    }
+
+    Ok(balances)
+}
+
+/// The wallet label that holds trading collateral for a signature type.
+pub fn funding_wallet_label(signature_type: SignatureType) -> &'static str {
+    match signature_type {
+        SignatureType::Eoa => "EOA",
+        SignatureType::GnosisSafe => "Safe",
+        _ => "Proxy",
+    }
+}
+
+/// Warn when collateral sits in a wallet the configured signature type doesn't trade from,
+/// or when the funding wallet only holds native USDC, which the CLOB doesn't accept.
+pub fn collateral_warnings(signature_type: SignatureType, wallets: &[WalletBalances]) -> Vec<String> {
+    let funding_label = funding_wallet_label(signature_type);
+    let mut warnings = Vec::new();
+
+    let Some(funding) = wallets.iter().find(|w| w.label == funding_label) else {
+        return warnings;
+    };
+
+    for wallet in wallets.iter().filter(|w| w.label != funding_label && !w.collateral().is_zero()) {
+        warnings.push(format!(
+            "{} USDC.e sits in the {} wallet, but signature type {} trades from the {} wallet ({})",
+            display_usdc(wallet.collateral()),
+            wallet.label,
+            signature_type,
+            funding_label,
+            funding.address
+        ));
+    }
+
+    if funding.collateral().is_zero() {
+        let native = funding.token("USDC (Native)");
+        if !native.is_zero() {
+            warnings.push(format!(
+                "{} wallet holds {} native USDC but no USDC.e; the CLOB only accepts USDC.e as collateral",
+                funding_label,
+                display_usdc(native)
+            ));
+        }
+    }
+
+    warnings
+}
+
+/// Format a raw POL balance (18 decimals) into a human-readable decimal.
+pub fn format_pol(raw_balance: U256) -> Result<Decimal> {
+    let pol = format_token_amount(raw_balance, 18).with_context(|| format!("POL balance {} is out of range", raw_balance))?;
+    Ok(pol.round_dp(6).normalize())
+}
+
+/// A raw USDC balance (6 decimals) as `status` prints it. A balance that can't be converted
+/// shows as an error rather than as zero.
+fn display_usdc(raw_balance: U256) -> String {
+    match format_token_amount(raw_balance, 6) {
+        Some(balance) => format!("${}", balance.normalize()),
+        None => format!("error (balance {} is out of range)", raw_balance),
+    }
+}
+
+/// A raw POL balance as `status` prints it, or the conversion error.
+fn display_pol(raw_balance: U256) -> String {
+    match format_pol(raw_balance) {
+        Ok(pol) => pol.to_string(),
+        Err(e) => format!("error ({})", e),
+    }
+}
+
+/// Format a raw token amount with the given number of decimals into a decimal.
+/// Returns `None` if the whole-unit part is too large to represent; excess fractional digits are rounded.
+pub fn format_token_amount(raw: U256, decimals: u8) -> Option<Decimal> {
+    let digits = raw.to_string();
+    let decimals = decimals as usize;
+    let (whole, fraction) = if digits.len() > decimals {
+        digits.split_at(digits.len() - decimals)
+    } else {
+        ("0", digits.as_str())
+    };
+    Decimal::from_str(&format!("{}.{:0>width$}", whole, fraction, width = decimals)).ok()
+}
+
 /// Format a raw token balance (with 6 decimals) into a human-readable decimal string.
-pub fn format_balance(raw_balance: alloy::primitives::U256) -> Decimal {
-    let balance_dec = Decimal::from_str(&raw_balance.to_string()).unwrap_or_default();
-    balance_dec / Decimal::from(1_000_000)
+pub fn format_balance(raw_balance: U256) -> Decimal {
+    format_token_amount(raw_balance, 6).unwrap_or_default().normalize()
+}
+
+/// Parse a human-readable amount (e.g. "12.5") into raw units with 6 decimals.
+pub fn parse_balance(amount: &str) -> Result<U256> {
+    parse_token_amount(amount, 6)
+}
+
+/// Parse a human-readable amount into raw units with the given number of decimals.
+/// Rejects zero, negative amounts and more fractional digits than the token has.
+pub fn parse_token_amount(amount: &str, decimals: u8) -> Result<U256> {
+    let value = Decimal::from_str(amount.trim()).with_context(|| format!("Invalid amount: {}", amount))?;
+    anyhow::ensure!(value > Decimal::ZERO, "Amount must be positive: {}", amount);
+    let value = value.normalize();
+    anyhow::ensure!(value.scale() <= decimals as u32, "Amount has more than {} decimals: {}", decimals, amount);
+
+    let digits = value.to_string();
+    let (whole, fraction) = digits.split_once('.').unwrap_or((&digits, ""));
+    let raw = format!("{}{:0<width$}", whole, fraction, width = decimals as usize);
+    U256::from_str(&raw).with_context(|| format!("Invalid amount: {}", amount))
 }
 
 /// Get the list of token names and addresses to query.
-#[allow(dead_code)]
 pub fn token_list() -> Vec<(&'static str, Address)> {
     vec![
         ("USDC.e", USDC_E_ADDRESS),
@@ -58,7 +301,6 @@ pub fn token_list() -> Vec<(&'static str, Address)> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use alloy::primitives::U256;
 
     #[test]
     fn test_format_balance_zero() {
@@ -99,6 +341,29 @@ mod tests {
         assert_eq!(formatted, Decimal::from_str("0.000001").unwrap());
     }
 
+    #[test]
+    fn test_parse_balance() {
+        assert_eq!(parse_balance("1").unwrap(), U256::from(1_000_000u64));
+        assert_eq!(parse_balance("4.281842").unwrap(), U256::from(4_281_842u64));
+        assert_eq!(parse_balance(" 0.5 ").unwrap(), U256::from(500_000u64));
+        assert_eq!(parse_balance("2.500000").unwrap(), U256::from(2_500_000u64));
+    }
+
+    #[test]
+    fn test_parse_balance_rejects_bad_amounts() {
+        assert!(parse_balance("0").is_err());
+        assert!(parse_balance("-1").is_err());
+        assert!(parse_balance("0.0000001").is_err());
+        assert!(parse_balance("abc").is_err());
+    }
+
+    #[test]
+    fn test_parse_token_amount_decimals() {
+        assert_eq!(parse_token_amount("1.5", 18).unwrap(), U256::from(1_500_000_000_000_000_000u64));
+        assert_eq!(parse_token_amount("42", 0).unwrap(), U256::from(42u64));
+        assert!(parse_token_amount("1.5", 0).is_err());
+    }
+
     #[test]
     fn test_token_list_has_two_entries() {
         let tokens = token_list();
@@ -113,4 +378,84 @@ mod tests {
         assert_eq!(tokens[0].1, USDC_E_ADDRESS);
         assert_eq!(tokens[1].1, USDC_NATIVE_ADDRESS);
     }
+
+    fn wallet(label: &'static str, usdc_e: u64, native: u64) -> WalletBalances {
+        WalletBalances {
+            label,
+            address: Address::ZERO,
+            tokens: vec![
+                ("USDC.e", Ok(U256::from(usdc_e))),
+                ("USDC (Native)", Ok(U256::from(native))),
+            ],
+            pol: Ok(U256::ZERO),
+        }
+    }
+
+    #[test]
+    fn test_format_token_amount_decimals() {
+        let raw = U256::from(1_234_500_000_000_000_000u64);
+        assert_eq!(format_token_amount(raw, 18), Some(Decimal::from_str("1.2345").unwrap()));
+        assert_eq!(format_token_amount(U256::from(5u64), 2), Some(Decimal::from_str("0.05").unwrap()));
+        assert_eq!(format_token_amount(U256::from(42u64), 0), Some(Decimal::from(42)));
+    }
+
+    #[test]
+    fn test_format_token_amount_overflow() {
+        assert_eq!(format_token_amount(U256::MAX, 6), None);
+        // Fractional digits beyond Decimal's precision are rounded, not rejected
+        assert!(format_token_amount(U256::from(1u64), 40).is_some());
+    }
+
+    #[test]
+    fn test_format_pol() {
+        let balance = U256::from(1_500_000_000_000_000_000u64);
+        assert_eq!(format_pol(balance).unwrap(), Decimal::from_str("1.5").unwrap());
+        assert_eq!(format_pol(U256::ZERO).unwrap(), Decimal::from(0));
+        assert!(format_pol(U256::MAX).is_err());
+    }
+
+    #[test]
+    fn test_display_balances_out_of_range() {
+        assert_eq!(display_usdc(U256::from(1_500_000u64)), "$1.5");
+        assert!(display_usdc(U256::MAX).starts_with("error ("));
+        assert!(display_pol(U256::MAX).starts_with("error ("));
+    }
+
+    #[test]
+    fn test_funding_wallet_label() {
+        assert_eq!(funding_wallet_label(SignatureType::Eoa), "EOA");
+        assert_eq!(funding_wallet_label(SignatureType::Proxy), "Proxy");
+        assert_eq!(funding_wallet_label(SignatureType::GnosisSafe), "Safe");
+    }
+
+    #[test]
+    fn test_collateral_warnings_funded_wallet_ok() {
+        let wallets = [wallet("EOA", 0, 0), wallet("Proxy", 1_000_000, 0)];
+        assert!(collateral_warnings(SignatureType::Proxy, &wallets).is_empty());
+    }
+
+    #[test]
+    fn test_collateral_warnings_dust_in_funding_wallet() {
+        // Most of the collateral is in the EOA, which proxy orders can't spend
+        let wallets = [wallet("EOA", 5_000_000_000, 0), wallet("Proxy", 10_000, 0)];
+        let warnings = collateral_warnings(SignatureType::Proxy, &wallets);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("$5000 USDC.e sits in the EOA wallet"));
+    }
+
+    #[test]
+    fn test_collateral_warnings_wrong_wallet() {
+        let wallets = [wallet("EOA", 5_000_000, 0), wallet("Proxy", 0, 0), wallet("Safe", 0, 0)];
+        let warnings = collateral_warnings(SignatureType::Proxy, &wallets);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("EOA wallet"));
+    }
+
+    #[test]
+    fn test_collateral_warnings_native_usdc_only() {
+        let wallets = [wallet("EOA", 0, 0), wallet("Proxy", 0, 2_000_000)];
+        let warnings = collateral_warnings(SignatureType::Proxy, &wallets);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("native USDC"));
+    }
 }
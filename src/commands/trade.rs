@@ -1,36 +1,492 @@
 use anyhow::{Context, Result};
-use polymarket_client_sdk::data::{
-    Client as DataClient,
-    types::{MarketFilter, request::TradesRequest},
+use polymarket_client_sdk::{
+    clob::types::Side,
+    data::{
+        Client as DataClient,
+        types::{MarketFilter, Side as TradeSide, request::TradesRequest, response::Trade},
+    },
+    types::{Address, DateTime, Decimal, Utc},
 };
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::time::Instant;
 
-pub async fn execute(token_id: String) -> Result<()> {
-    let client = DataClient::default();
-    let request = TradesRequest::builder()
-        .filter(MarketFilter::markets(vec![token_id.clone()]))
-        .limit(20)?
-        .build();
-    let trades = client.trades(&request).await.context("Failed to fetch trades")?;
+use crate::commands::history::{format_time, parse_time_bound};
+use crate::commands::market::MarketOutcomes;
+use crate::ws::{LastTradeMessage, MarketMessage, MarketStream, StreamEvent, market_channel_url};
+
+/// Trades fetched per data API page.
+const TRADES_PAGE_SIZE: i32 = 500;
+/// The data API rejects offsets above this.
+const MAX_TRADES_OFFSET: i32 = 10_000;
+
+pub async fn execute(
+    selected: MarketOutcomes,
+    user: Option<String>,
+    side: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    limit: Option<usize>,
+) -> Result<()> {
+    let user = user.map(|u| Address::from_str(&u).context("Invalid address format")).transpose()?;
+    let side = side.as_deref().map(parse_trade_side).transpose()?;
+    let now = Utc::now();
+    let from = from.as_deref().map(|v| parse_time_bound(v, now)).transpose()?;
+    let to = to.as_deref().map(|v| parse_time_bound(v, now)).transpose()?;
+    if let (Some(from), Some(to)) = (from, to) {
+        anyhow::ensure!(from < to, "--from must be before --to");
+    }
+    let condition_id = selected.market.condition_id.as_deref().context("Market has no condition ID")?;
+
+    let query = TradesQuery {
+        user,
+        side,
+        since: from.map(|f| f.timestamp()),
+        until: to.map(|t| t.timestamp()),
+        assets: selected.outcomes.iter().map(|(_, token)| token.clone()).collect(),
+        // A user's maker fills only show up with taker_only off
+        taker_only: user.is_none(),
+        limit,
+    };
+    let fetched = fetch_market_trades(condition_id, &query).await?;
+    let trades = &fetched.trades;
 
-    println!("Recent Trades for {}:", token_id);
+    println!("Trades for {}:", selected.market.question.as_deref().unwrap_or(&selected.market.id));
+    let width = trades.iter().map(|t| t.outcome.chars().count()).max().unwrap_or(0).max("Outcome".len());
+    println!(
+        "{:<16}  {:<width$}  {:<4}  {:>6}  {:>12}  {:<42}  Tx Hash",
+        "Time", "Outcome", "Side", "Price", "Size", "User",
+        width = width
+    );
     for trade in trades {
-        println!("- Trade: {:?}", trade);
+        println!(
+            "{:<16}  {:<width$}  {:<4}  {:>6}  {:>12}  {:<42}  {}",
+            format_time(trade.timestamp),
+            trade.outcome,
+            trade.side,
+            trade.price.normalize(),
+            trade.size.normalize(),
+            trade.proxy_wallet,
+            trade.transaction_hash,
+            width = width
+        );
+    }
+    let volume: Decimal = trades.iter().map(|t| t.size).sum();
+    let notional: Decimal = trades.iter().map(|t| t.size * t.price).sum();
+    let mut summary = format!(
+        "trades = {}, volume = {}, notional = {}",
+        trades.len(),
+        volume.normalize(),
+        notional.round_dp(2).normalize()
+    );
+    // Prices of different outcomes don't average into anything meaningful
+    if selected.outcomes.len() == 1 {
+        let vwap = if volume.is_zero() { "-".to_string() } else { (notional / volume).round_dp(4).normalize().to_string() };
+        summary.push_str(&format!(", vwap = {}", vwap));
+    }
+    println!("{}", summary);
+    if let Some(warning) = fetched.truncation_warning() {
+        eprintln!("{}; the totals above cover only the trades listed. Filter with --user or --side to reach further back.", warning);
+    }
+
+    Ok(())
+}
+
+pub fn parse_trade_side(value: &str) -> Result<TradeSide> {
+    match value.to_lowercase().as_str() {
+        "buy" => Ok(TradeSide::Buy),
+        "sell" => Ok(TradeSide::Sell),
+        _ => anyhow::bail!("Invalid side: must be 'buy' or 'sell'"),
+    }
+}
+
+/// Which trades of a market to fetch. User and side are filtered by the data API; the
+/// time window and outcome tokens here, as the API can't.
+#[derive(Debug, Clone, Default)]
+pub struct TradesQuery {
+    pub user: Option<Address>,
+    pub side: Option<TradeSide>,
+    /// Unix seconds, inclusive.
+    pub since: Option<i64>,
+    /// Unix seconds, exclusive.
+    pub until: Option<i64>,
+    /// Outcome token IDs to keep; empty keeps all.
+    pub assets: HashSet<String>,
+    pub taker_only: bool,
+    /// Stop after this many matching trades.
+    pub limit: Option<usize>,
+}
+
+impl TradesQuery {
+    /// Every trade of the given outcomes in a time window, each fill counted once (its taker
+    /// record), as needed to aggregate volume.
+    pub fn market_tape(since: i64, until: i64, assets: HashSet<String>) -> Self {
+        TradesQuery {
+            since: Some(since),
+            until: Some(until),
+            assets,
+            taker_only: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn matches(&self, trade: &Trade) -> bool {
+        self.since.is_none_or(|since| trade.timestamp >= since)
+            && self.until.is_none_or(|until| trade.timestamp < until)
+            && (self.assets.is_empty() || self.assets.contains(&trade.asset))
+    }
+}
+
+/// Print trades of the selected outcomes as they happen, from the WebSocket market channel,
+/// until interrupted. Then print volume and VWAP per outcome.
+pub async fn follow(selected: MarketOutcomes, min_size: Option<Decimal>) -> Result<()> {
+    let outcomes = selected.outcomes;
+    let names: HashMap<&str, &str> = outcomes.iter().map(|(name, token)| (token.as_str(), name.as_str())).collect();
+    let mut summaries: HashMap<&str, TapeSummary> = HashMap::new();
+    let mut stream = MarketStream::new(market_channel_url(), outcomes.iter().map(|(_, t)| t.clone()).collect());
+    let started = Instant::now();
+    let width = outcomes.iter().map(|(name, _)| name.chars().count()).max().unwrap_or(0).max("Outcome".len());
+
+    println!("Live trades for {} (Ctrl-C to stop):", selected.market.question.as_deref().unwrap_or(&selected.market.id));
+    println!(
+        "{:<23}  {:<width$}  {:<4}  {:>6}  {:>12}  {:>12}",
+        "Time", "Outcome", "Side", "Price", "Size", "Notional",
+        width = width
+    );
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    let mut connections: u32 = 0;
+    loop {
+        let event = tokio::select! {
+            event = stream.next() => event,
+            _ = &mut ctrl_c => break,
+        };
+        let trade = match event {
+            StreamEvent::Connected => {
+                connections += 1;
+                if connections > 1 {
+                    println!("{}", reconnect_marker(Utc::now()));
+                }
+                continue;
+            }
+            StreamEvent::Message(MarketMessage::LastTradePrice(trade)) => trade,
+            StreamEvent::Message(_) => continue,
+        };
+        let Some(name) = names.get(trade.asset_id.as_str()) else {
+            continue;
+        };
+        if !meets_min_size(&trade, min_size) {
+            continue;
+        }
+        println!("{}", tape_line(&trade, name, width));
+        summaries.entry(*name).or_default().record(&trade);
     }
 
+    println!();
+    println!(
+        "duration_secs = {}, reconnects = {}",
+        started.elapsed().as_secs(),
+        connections.saturating_sub(1)
+    );
+    let mut total = TapeSummary::default();
+    for (name, _) in &outcomes {
+        let summary = summaries.get(name.as_str()).cloned().unwrap_or_default();
+        println!("- outcome = {}, {}", name, summary);
+        total.merge(&summary);
+    }
+    // Prices of different outcomes don't average into anything meaningful, so no VWAP here
+    println!("total: {}", total.totals());
     Ok(())
 }
 
+/// Line printed in the tape after the stream reconnected: trades during the outage
+/// weren't received and won't be replayed.
+pub fn reconnect_marker(now: DateTime<Utc>) -> String {
+    format!("--- reconnected at {}, trades may be missing ---", now.format("%Y-%m-%d %H:%M:%S"))
+}
+
+/// Trades without a size only pass when there is no minimum.
+pub fn meets_min_size(trade: &LastTradeMessage, min_size: Option<Decimal>) -> bool {
+    match (min_size, trade.size) {
+        (None, _) => true,
+        (Some(min), Some(size)) => size >= min,
+        (Some(_), None) => false,
+    }
+}
+
+pub fn tape_line(trade: &LastTradeMessage, outcome: &str, width: usize) -> String {
+    let time = DateTime::from_timestamp_millis(trade.timestamp)
+        .map(|t| t.format("%Y-%m-%d %H:%M:%S%.3f").to_string())
+        .unwrap_or_else(|| trade.timestamp.to_string());
+    let side = trade.side.map(|s| s.to_string()).unwrap_or_else(|| "-".to_string());
+    let decimal = |d: Option<Decimal>| d.map(|d| d.normalize().to_string()).unwrap_or_else(|| "-".to_string());
+    format!(
+        "{:<23}  {:<width$}  {:<4}  {:>6}  {:>12}  {:>12}",
+        time,
+        outcome,
+        side,
+        trade.price.normalize(),
+        decimal(trade.size),
+        decimal(trade.size.map(|s| (s * trade.price).round_dp(2))),
+        width = width
+    )
+}
+
+/// Volume and VWAP of a stream of trades. Trades without a size aren't counted.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TapeSummary {
+    pub trades: usize,
+    pub volume: Decimal,
+    pub notional: Decimal,
+    /// Volume where the taker bought.
+    pub buy_volume: Decimal,
+    /// Volume where the taker sold.
+    pub sell_volume: Decimal,
+}
+
+impl TapeSummary {
+    pub fn record(&mut self, trade: &LastTradeMessage) {
+        let Some(size) = trade.size else {
+            return;
+        };
+        self.trades += 1;
+        self.volume += size;
+        self.notional += size * trade.price;
+        match trade.side {
+            Some(Side::Buy) => self.buy_volume += size,
+            Some(Side::Sell) => self.sell_volume += size,
+            _ => {}
+        }
+    }
+
+    pub fn merge(&mut self, other: &TapeSummary) {
+        self.trades += other.trades;
+        self.volume += other.volume;
+        self.notional += other.notional;
+        self.buy_volume += other.buy_volume;
+        self.sell_volume += other.sell_volume;
+    }
+
+    pub fn vwap(&self) -> Option<Decimal> {
+        (!self.volume.is_zero()).then(|| self.notional / self.volume)
+    }
+
+    /// The summary without the VWAP, for totals across outcomes.
+    pub fn totals(&self) -> String {
+        format!(
+            "trades = {}, volume = {}, notional = {}, buy_volume = {}, sell_volume = {}",
+            self.trades,
+            self.volume.normalize(),
+            self.notional.round_dp(2).normalize(),
+            self.buy_volume.normalize(),
+            self.sell_volume.normalize()
+        )
+    }
+}
+
+impl std::fmt::Display for TapeSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "trades = {}, volume = {}, notional = {}, vwap = {}, buy_volume = {}, sell_volume = {}",
+            self.trades,
+            self.volume.normalize(),
+            self.notional.round_dp(2).normalize(),
+            self.vwap().map(|v| v.round_dp(4).normalize().to_string()).unwrap_or_else(|| "-".to_string()),
+            self.buy_volume.normalize(),
+            self.sell_volume.normalize()
+        )
+    }
+}
+
+/// Trades of a market matching a query, newest first.
+#[derive(Debug, Clone, Default)]
+pub struct MarketTrades {
+    pub trades: Vec<Trade>,
+    /// Set when the data API's offset limit stopped the paging: trades before this Unix
+    /// time are missing.
+    pub missing_before: Option<i64>,
+}
+
+impl MarketTrades {
+    /// A warning for stderr when the trades don't reach back as far as asked.
+    pub fn truncation_warning(&self) -> Option<String> {
+        let before = self.missing_before?;
+        Some(format!(
+            "WARNING: truncated at {} trades: the data API pages back at most {} trades, so trades before {} are missing",
+            self.trades.len(),
+            MAX_TRADES_OFFSET + TRADES_PAGE_SIZE,
+            format_time(before)
+        ))
+    }
+}
+
+/// Trades of a market (by condition ID) matching `query`, newest first. Pages back until a
+/// trade older than `query.since` shows up, `query.limit` trades matched, or the data API's
+/// offset limit is reached. The API can't filter by time, so only `--user` and `--side`
+/// reach further back than that limit.
+pub async fn fetch_market_trades(condition_id: &str, query: &TradesQuery) -> Result<MarketTrades> {
+    let client = DataClient::default();
+    let mut trades = Vec::new();
+    let mut offset = 0;
+    let mut oldest = None;
+    while offset <= MAX_TRADES_OFFSET {
+        let request = TradesRequest::builder()
+            .filter(MarketFilter::markets(vec![condition_id.to_string()]))
+            .maybe_user(query.user)
+            .maybe_side(query.side)
+            .taker_only(query.taker_only)
+            .limit(TRADES_PAGE_SIZE)?
+            .offset(offset)?
+            .build();
+        let page = client.trades(&request).await.context("Failed to fetch trades")?;
+        let page_len = page.len() as i32;
+        let reached_since = query.since.is_some_and(|since| page.iter().any(|t| t.timestamp < since));
+        oldest = page.iter().map(|t| t.timestamp).min().or(oldest);
+        trades.extend(page.into_iter().filter(|t| query.matches(t)));
+        if let Some(limit) = query.limit.filter(|limit| trades.len() >= *limit) {
+            trades.truncate(limit);
+            return Ok(MarketTrades { trades, missing_before: None });
+        }
+        if reached_since || page_len < TRADES_PAGE_SIZE {
+            return Ok(MarketTrades { trades, missing_before: None });
+        }
+        offset += page_len;
+    }
+    Ok(MarketTrades { trades, missing_before: oldest })
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     // Trade command primarily wraps the SDK client.
     // Integration tests would require a live API connection.
     // We verify the module compiles and the public API surface is correct.
 
     #[test]
+    #[allow(clippy::assertions_on_constants, clippy::type_complexity)]
     fn test_module_compiles() {
         // Ensure the execute function signature is correct
         fn _assert_fn_signature(_: fn(String) -> std::pin::Pin<Box<dyn std::future::Future<Output = anyhow::Result<()>>>>) {}
         // This test just ensures the module is well-formed.
         assert!(true);
     }
+
+    #[test]
+    fn test_parse_trade_side() {
+        assert_eq!(parse_trade_side("BUY").unwrap(), TradeSide::Buy);
+        assert_eq!(parse_trade_side("sell").unwrap(), TradeSide::Sell);
+        assert!(parse_trade_side("short").is_err());
+    }
+
+    #[test]
+    fn test_trades_query_matches() {
+        let trade = |asset: &str, timestamp: i64| -> Trade {
+            serde_json::from_value(serde_json::json!({
+                "proxyWallet": "0x76564A875522c78263B7c0c51B3760A1776877af",
+                "side": "BUY",
+                "asset": asset,
+                "conditionId": "0xdd22472e552920b8438158ea7238bfadfa4f736aa4cee91a6b86c39ead110917",
+                "size": 10,
+                "price": 0.5,
+                "timestamp": timestamp,
+                "title": "Will it rain?",
+                "slug": "will-it-rain",
+                "icon": "",
+                "eventSlug": "will-it-rain",
+                "outcome": "Yes",
+                "outcomeIndex": 0,
+                "transactionHash": "0xabc"
+            }))
+            .unwrap()
+        };
+        let query = TradesQuery {
+            since: Some(100),
+            until: Some(200),
+            assets: HashSet::from(["111".to_string()]),
+            ..Default::default()
+        };
+        assert!(query.matches(&trade("111", 100)));
+        assert!(!query.matches(&trade("111", 99)));
+        assert!(!query.matches(&trade("111", 200)));
+        assert!(!query.matches(&trade("222", 150)));
+        assert!(TradesQuery::default().matches(&trade("222", 0)));
+    }
+
+    #[test]
+    fn test_market_tape_is_taker_only() {
+        let query = TradesQuery::market_tape(100, 200, HashSet::from(["111".to_string()]));
+        assert!(query.taker_only);
+        assert_eq!((query.since, query.until), (Some(100), Some(200)));
+        assert!(query.user.is_none() && query.side.is_none() && query.limit.is_none());
+    }
+
+    #[test]
+    fn test_truncation_warning() {
+        assert_eq!(MarketTrades::default().truncation_warning(), None);
+        let truncated = MarketTrades { trades: Vec::new(), missing_before: Some(1_718_000_000) };
+        let warning = truncated.truncation_warning().unwrap();
+        assert!(warning.contains("truncated at 0 trades"), "{}", warning);
+        assert!(warning.contains("before 2024-06-10 06:13"), "{}", warning);
+    }
+
+    fn trade(side: &str, price: &str, size: Option<&str>) -> LastTradeMessage {
+        serde_json::from_value(serde_json::json!({
+            "asset_id": "1",
+            "price": price,
+            "side": side,
+            "size": size,
+            "timestamp": "1700000000250"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_tape_summary_vwap() {
+        let mut summary = TapeSummary::default();
+        summary.record(&trade("BUY", "0.50", Some("100")));
+        summary.record(&trade("SELL", "0.60", Some("300")));
+        summary.record(&trade("BUY", "0.70", None));
+        assert_eq!(summary.trades, 2);
+        assert_eq!(summary.volume, Decimal::from(400));
+        assert_eq!(summary.vwap(), Some(Decimal::new(575, 3)));
+        assert_eq!(
+            summary.to_string(),
+            "trades = 2, volume = 400, notional = 230, vwap = 0.575, buy_volume = 100, sell_volume = 300"
+        );
+        assert_eq!(TapeSummary::default().vwap(), None);
+    }
+
+    #[test]
+    fn test_tape_summary_totals_have_no_vwap() {
+        let mut yes = TapeSummary::default();
+        yes.record(&trade("BUY", "0.30", Some("100")));
+        let mut total = TapeSummary::default();
+        total.merge(&yes);
+        total.merge(&TapeSummary { trades: 1, volume: Decimal::from(100), notional: Decimal::from(70), ..Default::default() });
+        assert_eq!(total.totals(), "trades = 2, volume = 200, notional = 100, buy_volume = 100, sell_volume = 0");
+    }
+
+    #[test]
+    fn test_reconnect_marker() {
+        let now = DateTime::parse_from_rfc3339("2024-06-08T12:00:05Z").unwrap().with_timezone(&Utc);
+        assert_eq!(reconnect_marker(now), "--- reconnected at 2024-06-08 12:00:05, trades may be missing ---");
+    }
+
+    #[test]
+    fn test_meets_min_size() {
+        assert!(meets_min_size(&trade("BUY", "0.5", None), None));
+        assert!(!meets_min_size(&trade("BUY", "0.5", None), Some(Decimal::ONE)));
+        assert!(meets_min_size(&trade("BUY", "0.5", Some("10")), Some(Decimal::TEN)));
+        assert!(!meets_min_size(&trade("BUY", "0.5", Some("9.99")), Some(Decimal::TEN)));
+    }
+
+    #[test]
+    fn test_tape_line() {
+        assert_eq!(
+            tape_line(&trade("SELL", "0.550", Some("120.5")), "Yes", 7),
+            "2023-11-14 22:13:20.250  Yes      SELL    0.55         120.5         66.28"
+        );
+    }
 }
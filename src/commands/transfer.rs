@@ -0,0 +1,205 @@
+use anyhow::{Context, Result};
+use alloy::primitives::{Bytes, U256};
+use alloy::providers::ProviderBuilder;
+use alloy::sol_types::SolCall;
+use polymarket_client_sdk::{
+    POLYGON, PRIVATE_KEY_VAR,
+    auth::{LocalSigner, Signer},
+    clob::types::SignatureType,
+    contract_config,
+    types::Address,
+};
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+use crate::commands::balance::{TokenCache, fetch_token_balances};
+use crate::commands::status::{format_token_amount, funding_wallet_label, parse_token_amount};
+use crate::constants::USDC_E_ADDRESS;
+use crate::contracts::{IERC20, IERC1155, new_erc1155};
+use crate::rpc;
+use crate::wallet::{funder_address, send_call, parse_signature_type, signature_type_from_env};
+
+/// Outcome tokens use the same 6 decimals as USDC.
+const OUTCOME_TOKEN_DECIMALS: u8 = 6;
+
+pub async fn execute(
+    to: String,
+    amount: String,
+    token: Option<String>,
+    token_id: Option<String>,
+    from: Option<String>,
+    yes: bool,
+    rpc_urls: &[String],
+) -> Result<()> {
+    let private_key = env::var(PRIVATE_KEY_VAR).context("Need PRIVATE_KEY environment variable")?;
+    let signer = LocalSigner::from_str(&private_key)?.with_chain_id(Some(POLYGON));
+    let owner = signer.address();
+    let signature_type = match from {
+        Some(from) => parse_signature_type(&from)?,
+        None => signature_type_from_env()?,
+    };
+    let source = funder_address(owner, signature_type)?;
+    let recipient = parse_recipient(&to)?;
+    anyhow::ensure!(recipient != source, "Recipient is the sending wallet {}", source);
+
+    let provider = ProviderBuilder::new()
+        .wallet(signer.clone())
+        .connect_client(rpc::connect(rpc_urls).await?);
+
+    let config = contract_config(POLYGON, false).context("Failed to get contract config")?;
+    let (asset, target, decimals, balance) = match token_id {
+        Some(token_id) => {
+            let id = U256::from_str(&token_id).context("Invalid token ID")?;
+            let ctf = new_erc1155(config.conditional_tokens, provider.clone());
+            let balance = ctf.balanceOf(source, id).call().await.context("Failed to read outcome token balance")?;
+            (Asset::Outcome(id), config.conditional_tokens, OUTCOME_TOKEN_DECIMALS, balance)
+        }
+        None => {
+            let token = match token {
+                Some(token) => Address::from_str(&token).with_context(|| format!("Invalid token address: {}", token))?,
+                None => USDC_E_ADDRESS,
+            };
+            let mut cache = TokenCache::load();
+            let balances = fetch_token_balances(&provider, &mut cache, source, &[token]).await?;
+            if let Err(e) = cache.save() {
+                log::warn!("error = {:#}, failed to save token metadata cache", e);
+            }
+            let (_, metadata, balance) = balances.into_iter().next().context("Missing token balance")?;
+            let balance = balance.context("Failed to read token balance")?;
+            (Asset::Erc20(metadata.symbol), token, metadata.decimals, balance)
+        }
+    };
+
+    let raw_amount = parse_token_amount(&amount, decimals)?;
+    let display = |raw: U256| {
+        format_token_amount(raw, decimals)
+            .map(|d| d.normalize().to_string())
+            .unwrap_or_else(|| raw.to_string())
+    };
+    anyhow::ensure!(
+        balance >= raw_amount,
+        "Not enough {} in {}: have {}, need {}",
+        asset,
+        source,
+        display(balance),
+        display(raw_amount)
+    );
+
+    println!("from = {} ({}), to = {}", source, funding_wallet_label(signature_type), recipient);
+    println!("asset = {}, amount = {}, balance = {}", asset, display(raw_amount), display(balance));
+    if !own_wallets(owner)?.contains(&recipient) {
+        println!("WARNING: {} is not one of this key's wallets (EOA, Proxy, Safe)", recipient);
+    }
+
+    if !yes && !confirm("Send this transfer?")? {
+        println!("Aborted.");
+        return Ok(());
+    }
+
+    let data = match &asset {
+        Asset::Erc20(_) => erc20_transfer_calldata(recipient, raw_amount),
+        Asset::Outcome(id) => outcome_transfer_calldata(source, recipient, *id, raw_amount),
+    };
+    let tx_hash = send_call(&provider, owner, signature_type, target, data).await?;
+    println!("tx = {}, transferred", tx_hash);
+
+    Ok(())
+}
+
+/// What is being transferred: an ERC-20 token (by symbol) or a CTF outcome token (by position ID).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Asset {
+    Erc20(String),
+    Outcome(U256),
+}
+
+impl std::fmt::Display for Asset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Asset::Erc20(symbol) => write!(f, "{}", symbol),
+            Asset::Outcome(id) => write!(f, "outcome token {}", id),
+        }
+    }
+}
+
+/// Parse a recipient address. Mixed-case input must carry a valid EIP-55 checksum, so a
+/// mistyped address is caught instead of silently losing funds.
+pub fn parse_recipient(value: &str) -> Result<Address> {
+    let value = value.trim();
+    let hex = value.strip_prefix("0x").unwrap_or(value);
+    let mixed_case = hex.chars().any(|c| c.is_ascii_uppercase()) && hex.chars().any(|c| c.is_ascii_lowercase());
+    let address = if mixed_case {
+        Address::parse_checksummed(value, None).with_context(|| format!("Invalid address checksum: {}", value))?
+    } else {
+        Address::from_str(value).with_context(|| format!("Invalid address: {}", value))?
+    };
+    anyhow::ensure!(address != Address::ZERO, "Refusing to transfer to the zero address");
+    Ok(address)
+}
+
+/// The EOA and the proxy and Safe wallets it controls.
+fn own_wallets(owner: Address) -> Result<Vec<Address>> {
+    Ok(vec![
+        owner,
+        funder_address(owner, SignatureType::Proxy)?,
+        funder_address(owner, SignatureType::GnosisSafe)?,
+    ])
+}
+
+/// Ask a yes/no question on the terminal. Anything but "y" or "yes" means no.
+pub fn confirm(question: &str) -> Result<bool> {
+    print!("{} [y/N] ", question);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer).context("Failed to read answer")?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+pub fn erc20_transfer_calldata(to: Address, amount: U256) -> Bytes {
+    IERC20::transferCall { to, value: amount }.abi_encode().into()
+}
+
+pub fn outcome_transfer_calldata(from: Address, to: Address, id: U256, amount: U256) -> Bytes {
+    IERC1155::safeTransferFromCall { from, to, id, amount, data: Bytes::new() }.abi_encode().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_recipient_checksum() {
+        let checksummed = "0x76564A875522c78263B7c0c51B3760A1776877af";
+        assert_eq!(parse_recipient(checksummed).unwrap().to_string(), checksummed);
+        assert!(parse_recipient(&checksummed.to_lowercase()).is_ok());
+        // One flipped letter case breaks the checksum
+        assert!(parse_recipient("0x76564a875522c78263B7c0c51B3760A1776877af").is_err());
+    }
+
+    #[test]
+    fn test_parse_recipient_rejects_zero_and_garbage() {
+        assert!(parse_recipient("0x0000000000000000000000000000000000000000").is_err());
+        assert!(parse_recipient("not_an_address").is_err());
+    }
+
+    #[test]
+    fn test_erc20_transfer_calldata() {
+        let to = Address::repeat_byte(0x22);
+        let decoded = IERC20::transferCall::abi_decode(&erc20_transfer_calldata(to, U256::from(5u64))).unwrap();
+        assert_eq!(decoded.to, to);
+        assert_eq!(decoded.value, U256::from(5u64));
+    }
+
+    #[test]
+    fn test_outcome_transfer_calldata() {
+        let from = Address::repeat_byte(0x11);
+        let to = Address::repeat_byte(0x22);
+        let data = outcome_transfer_calldata(from, to, U256::from(42u64), U256::from(5u64));
+        let decoded = IERC1155::safeTransferFromCall::abi_decode(&data).unwrap();
+        assert_eq!(decoded.from, from);
+        assert_eq!(decoded.to, to);
+        assert_eq!(decoded.id, U256::from(42u64));
+        assert!(decoded.data.is_empty());
+    }
+}
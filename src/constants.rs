@@ -1,6 +1,18 @@
 use polymarket_client_sdk::types::{Address, address};
 
-pub const RPC_URL: &str = "https://polygon-bor-rpc.publicnode.com";
+/// Default Polygon RPC endpoints, tried in order.
+pub const RPC_URLS: &[&str] = &[
+    "https://polygon-bor-rpc.publicnode.com",
+    "https://polygon-rpc.com",
+    "https://polygon.drpc.org",
+    "https://1rpc.io/matic",
+];
 
 pub const USDC_E_ADDRESS: Address = address!("0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174");
 pub const USDC_NATIVE_ADDRESS: Address = address!("0x3c499c542cEF5E3811e1192ce70d8cC03d5c3359");
+
+/// Wrapped USDC.e used as collateral by neg-risk markets' positions.
+pub const NEG_RISK_WRAPPED_COLLATERAL_ADDRESS: Address = address!("0x3A3BD7bb9528E159577F7C2e685CC81A765002E2");
+
+/// Multicall3, deployed at the same address on every EVM chain.
+pub const MULTICALL3_ADDRESS: Address = address!("0xcA11bde05977b3631167028862bE2a173976CA11");
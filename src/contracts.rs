@@ -1,20 +1,112 @@
 use alloy::primitives::U256;
 use alloy::sol;
-use anyhow::Result;
+use alloy::sol_types::SolCall;
+use anyhow::{Context, Result};
 use polymarket_client_sdk::types::Address;
 
+use crate::constants::MULTICALL3_ADDRESS;
+
 sol! {
     #[sol(rpc)]
     interface IERC20 {
         function approve(address spender, uint256 value) external returns (bool);
+        function transfer(address to, uint256 value) external returns (bool);
         function allowance(address owner, address spender) external view returns (uint256);
         function balanceOf(address account) external view returns (uint256);
+        function decimals() external view returns (uint8);
+        function symbol() external view returns (string);
+
+        event Approval(address indexed owner, address indexed spender, uint256 value);
     }
 
     #[sol(rpc)]
     interface IERC1155 {
         function setApprovalForAll(address operator, bool approved) external;
         function isApprovedForAll(address account, address operator) external view returns (bool);
+        function balanceOf(address account, uint256 id) external view returns (uint256);
+        function balanceOfBatch(address[] accounts, uint256[] ids) external view returns (uint256[]);
+        function safeTransferFrom(address from, address to, uint256 id, uint256 amount, bytes data) external;
+
+        event ApprovalForAll(address indexed account, address indexed operator, bool approved);
+    }
+
+    #[sol(rpc)]
+    interface IConditionalTokens {
+        function splitPosition(address collateralToken, bytes32 parentCollectionId, bytes32 conditionId, uint256[] partition, uint256 amount) external;
+        function mergePositions(address collateralToken, bytes32 parentCollectionId, bytes32 conditionId, uint256[] partition, uint256 amount) external;
+        function redeemPositions(address collateralToken, bytes32 parentCollectionId, bytes32 conditionId, uint256[] indexSets) external;
+        function payoutDenominator(bytes32 conditionId) external view returns (uint256);
+        function payoutNumerators(bytes32 conditionId, uint256 index) external view returns (uint256);
+    }
+
+    #[sol(rpc)]
+    interface INegRiskAdapter {
+        function splitPosition(bytes32 conditionId, uint256 amount) external;
+        function mergePositions(bytes32 conditionId, uint256 amount) external;
+        function convertPositions(bytes32 marketId, uint256 indexSet, uint256 amount) external;
+        function getQuestionCount(bytes32 marketId) external view returns (uint256);
+        function getFeeBips(bytes32 marketId) external view returns (uint256);
+        function getPositionId(bytes32 questionId, bool outcome) external view returns (uint256);
+        function redeemPositions(bytes32 conditionId, uint256[] amounts) external;
+    }
+
+    #[sol(rpc)]
+    interface IProxyWalletFactory {
+        struct ProxyCall {
+            uint8 typeCode;
+            address to;
+            uint256 value;
+            bytes data;
+        }
+
+        function proxy(ProxyCall[] calls) external payable returns (bytes[] returnValues);
+    }
+
+    // Only used to encode calls routed through a Safe, so no RPC bindings.
+    interface IGnosisSafe {
+        function execTransaction(
+            address to,
+            uint256 value,
+            bytes data,
+            uint8 operation,
+            uint256 safeTxGas,
+            uint256 baseGas,
+            uint256 gasPrice,
+            address gasToken,
+            address refundReceiver,
+            bytes signatures
+        ) external payable returns (bool success);
+    }
+
+    // Only used to decode fill events, so no RPC bindings.
+    interface ICtfExchange {
+        event OrderFilled(
+            bytes32 indexed orderHash,
+            address indexed maker,
+            address indexed taker,
+            uint256 makerAssetId,
+            uint256 takerAssetId,
+            uint256 makerAmountFilled,
+            uint256 takerAmountFilled,
+            uint256 fee
+        );
+    }
+
+    #[sol(rpc)]
+    interface IMulticall3 {
+        struct Call3 {
+            address target;
+            bool allowFailure;
+            bytes callData;
+        }
+
+        struct Result {
+            bool success;
+            bytes returnData;
+        }
+
+        function aggregate3(Call3[] calldata calls) external payable returns (Result[] memory returnData);
+        function getEthBalance(address addr) external view returns (uint256 balance);
     }
 }
 
@@ -26,32 +118,6 @@ pub fn new_erc1155<P: alloy::providers::Provider + Clone>(address: Address, prov
     IERC1155::new(address, provider)
 }
 
-pub async fn check_allowance<P: alloy::providers::Provider>(
-    token: &IERC20::IERC20Instance<P>,
-    owner: Address,
-    spender: Address,
-) -> Result<U256> {
-    let allowance = token.allowance(owner, spender).call().await?;
-    Ok(allowance)
-}
-
-pub async fn check_balance<P: alloy::providers::Provider>(
-    token: &IERC20::IERC20Instance<P>,
-    account: Address,
-) -> Result<U256> {
-    let balance = token.balanceOf(account).call().await?;
-    Ok(balance)
-}
-
-pub async fn check_approval_for_all<P: alloy::providers::Provider>(
-    ctf: &IERC1155::IERC1155Instance<P>,
-    account: Address,
-    operator: Address,
-) -> Result<bool> {
-    let approved = ctf.isApprovedForAll(account, operator).call().await?;
-    Ok(approved)
-}
-
 pub async fn approve_token<P: alloy::providers::Provider>(
     usdc: &IERC20::IERC20Instance<P>,
     spender: Address,
@@ -74,3 +140,106 @@ pub async fn set_approval_for_all<P: alloy::providers::Provider>(
         .await?;
     Ok(tx_hash)
 }
+
+/// Token IDs per `balanceOfBatch` call, to keep responses within RPC limits.
+const BALANCE_BATCH_SIZE: usize = 200;
+
+/// Read one account's balance of many ERC-1155 token IDs with `balanceOfBatch`.
+/// Balances are returned in the same order as the IDs.
+pub async fn balance_of_batch<P: alloy::providers::Provider>(
+    provider: &P,
+    token: Address,
+    account: Address,
+    ids: &[U256],
+) -> Result<Vec<U256>> {
+    let contract = IERC1155::new(token, provider);
+    let mut balances = Vec::with_capacity(ids.len());
+    for chunk in ids.chunks(BALANCE_BATCH_SIZE) {
+        let accounts = vec![account; chunk.len()];
+        let batch = contract
+            .balanceOfBatch(accounts, chunk.to_vec())
+            .call()
+            .await
+            .context("ERC-1155 balanceOfBatch failed")?;
+        anyhow::ensure!(batch.len() == chunk.len(), "balanceOfBatch returned {} balances for {} IDs", batch.len(), chunk.len());
+        balances.extend(batch);
+    }
+    Ok(balances)
+}
+
+/// Build a Multicall3 call entry for a typed contract call. Failures are allowed,
+/// so one reverting read doesn't fail the whole batch.
+pub fn call3<C: SolCall>(target: Address, call: C) -> IMulticall3::Call3 {
+    IMulticall3::Call3 {
+        target,
+        allowFailure: true,
+        callData: call.abi_encode().into(),
+    }
+}
+
+/// Execute a batch of read-only calls in a single `eth_call` through Multicall3 `aggregate3`.
+/// Results are returned in the same order as the calls.
+pub async fn aggregate3<P: alloy::providers::Provider>(
+    provider: &P,
+    calls: Vec<IMulticall3::Call3>,
+) -> Result<Vec<IMulticall3::Result>> {
+    if calls.is_empty() {
+        return Ok(Vec::new());
+    }
+    let count = calls.len();
+    let multicall = IMulticall3::new(MULTICALL3_ADDRESS, provider);
+    let results = multicall.aggregate3(calls).call().await.context("Multicall3 aggregate3 failed")?;
+    anyhow::ensure!(results.len() == count, "Multicall3 returned {} results for {} calls", results.len(), count);
+    Ok(results)
+}
+
+/// Decode one `aggregate3` result as the return value of the given call type.
+pub fn decode_result<C: SolCall>(result: &IMulticall3::Result) -> Result<C::Return> {
+    anyhow::ensure!(result.success, "call reverted");
+    C::abi_decode_returns(&result.returnData).context("Failed to decode call result")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::sol_types::SolValue;
+
+    #[test]
+    fn test_call3_encodes_calldata() {
+        let token = Address::repeat_byte(0x11);
+        let account = Address::repeat_byte(0x22);
+        let call = call3(token, IERC20::balanceOfCall { account });
+        assert_eq!(call.target, token);
+        assert!(call.allowFailure);
+        assert_eq!(call.callData.as_ref(), IERC20::balanceOfCall { account }.abi_encode().as_slice());
+    }
+
+    #[test]
+    fn test_decode_result_success() {
+        let result = IMulticall3::Result {
+            success: true,
+            returnData: U256::from(42u64).abi_encode().into(),
+        };
+        let decoded = decode_result::<IERC20::balanceOfCall>(&result).unwrap();
+        assert_eq!(decoded, U256::from(42u64));
+    }
+
+    #[test]
+    fn test_decode_result_reverted() {
+        let result = IMulticall3::Result {
+            success: false,
+            returnData: Default::default(),
+        };
+        assert!(decode_result::<IERC20::balanceOfCall>(&result).is_err());
+    }
+
+    #[test]
+    fn test_decode_result_bool() {
+        let result = IMulticall3::Result {
+            success: true,
+            returnData: true.abi_encode().into(),
+        };
+        assert!(decode_result::<IERC1155::isApprovedForAllCall>(&result).unwrap());
+    }
+}
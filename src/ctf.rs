@@ -0,0 +1,166 @@
+//! Conditional Tokens ID derivation, computed locally the same way the CTF contracts do.
+
+use alloy::primitives::{B256, U256, keccak256, uint};
+use polymarket_client_sdk::types::Address;
+
+/// Field modulus of alt_bn128, the curve the CTF hashes collection IDs onto.
+const P: U256 = uint!(0x30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47_U256);
+/// Curve equation constant: `y² = x³ + 3`.
+const B: U256 = uint!(3_U256);
+
+/// `keccak256(oracle ‖ questionId ‖ outcomeSlotCount)`.
+pub fn condition_id(oracle: Address, question_id: B256, outcome_count: u32) -> B256 {
+    let mut data = Vec::with_capacity(84);
+    data.extend_from_slice(oracle.as_slice());
+    data.extend_from_slice(question_id.as_slice());
+    data.extend_from_slice(&U256::from(outcome_count).to_be_bytes::<32>());
+    keccak256(data)
+}
+
+/// Collection ID of a top-level position (empty parent collection), as in `CTHelpers.getCollectionId`.
+///
+/// The hash of the condition and index set is mapped to a point on alt_bn128 so that
+/// collections can be combined by point addition; the ID is the point's x coordinate
+/// with the parity of y folded into bit 254.
+pub fn collection_id(condition_id: B256, index_set: U256) -> B256 {
+    let mut data = Vec::with_capacity(64);
+    data.extend_from_slice(condition_id.as_slice());
+    data.extend_from_slice(&index_set.to_be_bytes::<32>());
+    let mut x = U256::from_be_bytes(keccak256(data).0);
+    let odd = x.bit(255);
+
+    let mut y;
+    loop {
+        x = x.add_mod(U256::from(1), P);
+        let yy = x.mul_mod(x.mul_mod(x, P), P).add_mod(B, P);
+        y = sqrt_mod(yy);
+        if y.mul_mod(y, P) == yy {
+            break;
+        }
+    }
+    if odd != y.bit(0) {
+        y = P - y;
+    }
+    if y.bit(0) {
+        x ^= U256::from(1) << 254;
+    }
+    B256::from(x)
+}
+
+/// `keccak256(collateralToken ‖ collectionId)`, the ERC-1155 token ID of a position.
+pub fn position_id(collateral: Address, collection_id: B256) -> U256 {
+    let mut data = Vec::with_capacity(52);
+    data.extend_from_slice(collateral.as_slice());
+    data.extend_from_slice(collection_id.as_slice());
+    U256::from_be_bytes(keccak256(data).0)
+}
+
+/// Index set selecting a single outcome slot.
+pub fn outcome_index_set(outcome_index: u32) -> U256 {
+    U256::from(1) << outcome_index as usize
+}
+
+/// Square root modulo `P`, which is 3 mod 4. Only meaningful when `value` is a quadratic residue.
+fn sqrt_mod(value: U256) -> U256 {
+    value.pow_mod((P + U256::from(1)) >> 2, P)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::b256;
+    use crate::constants::{NEG_RISK_WRAPPED_COLLATERAL_ADDRESS, USDC_E_ADDRESS};
+    use polymarket_client_sdk::types::address;
+
+    const ORACLE: Address = address!("0x6A9D222616C90FcA5754cd1333cFD9b7fb6a4F74");
+
+    #[test]
+    fn test_condition_id_depends_on_every_input() {
+        let question = B256::repeat_byte(0x01);
+        let id = condition_id(ORACLE, question, 2);
+        assert_ne!(id, condition_id(ORACLE, question, 3));
+        assert_ne!(id, condition_id(Address::ZERO, question, 2));
+        assert_ne!(id, condition_id(ORACLE, B256::repeat_byte(0x02), 2));
+    }
+
+    #[test]
+    fn test_collection_id_is_a_curve_point() {
+        let condition = condition_id(ORACLE, B256::repeat_byte(0x01), 2);
+        for index_set in [1u64, 2] {
+            let id = U256::from_be_bytes(collection_id(condition, U256::from(index_set)).0);
+            // Clear the parity flag to recover x, then check x³ + 3 has a square root.
+            let mut x = id;
+            x.set_bit(254, false);
+            assert!(x < P);
+            let yy = x.mul_mod(x.mul_mod(x, P), P).add_mod(B, P);
+            let y = sqrt_mod(yy);
+            assert_eq!(y.mul_mod(y, P), yy);
+        }
+    }
+
+    #[test]
+    fn test_collection_ids_differ_per_outcome() {
+        let condition = condition_id(ORACLE, B256::repeat_byte(0x01), 2);
+        assert_ne!(
+            collection_id(condition, outcome_index_set(0)),
+            collection_id(condition, outcome_index_set(1))
+        );
+    }
+
+    /// Yes and No token IDs of a standard CTF market ("Will NVIDIA (NVDA) close above $240 end of
+    /// January?"), collateralised by USDC.e.
+    #[test]
+    fn test_known_ctf_market_token_ids() {
+        let condition = b256!("0x311d0c4b6671ab54af4970c06fcf58662516f5168997bdda209ec3db5aa6b0c1");
+        let yes = collection_id(condition, outcome_index_set(0));
+        let no = collection_id(condition, outcome_index_set(1));
+        assert_eq!(
+            position_id(USDC_E_ADDRESS, yes),
+            uint!(76043073756653678226373981964075571318267289248134717369284518995922789326425_U256)
+        );
+        assert_eq!(
+            position_id(USDC_E_ADDRESS, no),
+            uint!(31690934263385727664202099278545688007799199447969475608906331829650099442770_U256)
+        );
+    }
+
+    /// Yes and No token IDs of a neg-risk market ("Will Donald Trump win the 2024 US Presidential
+    /// Election?"), whose positions are backed by the Neg Risk Adapter's wrapped collateral.
+    #[test]
+    fn test_known_neg_risk_market_token_ids() {
+        let condition = b256!("0xdd22472e552920b8438158ea7238bfadfa4f736aa4cee91a6b86c39ead110917");
+        let yes = collection_id(condition, outcome_index_set(0));
+        let no = collection_id(condition, outcome_index_set(1));
+        // Only the No collection has an odd y, so only it carries the parity flag in bit 254
+        assert_eq!(yes, b256!("0x13c5bd8e1449325256f875332875131b41b7ff90b7ec816a38259785663eb2d8"));
+        assert_eq!(no, b256!("0x679fe1f869287ffd909cce0e21e144a5801ccc3a6934d482ce01f8bacf9ed144"));
+        assert_eq!(
+            position_id(NEG_RISK_WRAPPED_COLLATERAL_ADDRESS, yes),
+            uint!(21742633143463906290569050155826241533067272736897614950488156847949938836455_U256)
+        );
+        assert_eq!(
+            position_id(NEG_RISK_WRAPPED_COLLATERAL_ADDRESS, no),
+            uint!(48331043336612883890938759509493159234755048973500640148014422747788308965732_U256)
+        );
+    }
+
+    #[test]
+    fn test_position_id_depends_on_collateral() {
+        let collection = B256::repeat_byte(0x07);
+        assert_ne!(position_id(ORACLE, collection), position_id(Address::ZERO, collection));
+    }
+
+    #[test]
+    fn test_outcome_index_set() {
+        assert_eq!(outcome_index_set(0), U256::from(1));
+        assert_eq!(outcome_index_set(1), U256::from(2));
+        assert_eq!(outcome_index_set(3), U256::from(8));
+    }
+
+    #[test]
+    fn test_sqrt_mod() {
+        let square = U256::from(12345u64).mul_mod(U256::from(12345u64), P);
+        let root = sqrt_mod(square);
+        assert_eq!(root.mul_mod(root, P), square);
+    }
+}
@@ -1,7 +1,12 @@
+mod chart;
 mod cli;
 mod commands;
 mod constants;
 mod contracts;
+mod ctf;
+mod rpc;
+mod wallet;
+mod ws;
 
 use clap::Parser;
 use cli::{Cli, Commands};
@@ -10,21 +15,98 @@ use anyhow::Result;
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenv::dotenv().ok();
-    env_logger::init();
-
     let cli = Cli::parse();
 
+    // Warnings are shown by default; --verbose adds this crate's info lines.
+    let filter = if cli.verbose { concat!("warn,", env!("CARGO_CRATE_NAME"), "=info") } else { "warn" };
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(filter)).init();
+
     match cli.command {
-        Commands::Search { query } => commands::search::execute(query).await,
+        Commands::Search {
+            query,
+            status,
+            tag,
+            min_liquidity,
+            min_volume,
+            ends_before,
+            ends_after,
+            sort,
+            limit,
+            page,
+            compact,
+        } => {
+            commands::search::execute(
+                query,
+                status,
+                tag,
+                min_liquidity,
+                min_volume,
+                ends_before,
+                ends_after,
+                sort,
+                limit,
+                page,
+                compact,
+            )
+            .await
+        }
+        Commands::Market { market } => commands::market::execute(market).await,
+        Commands::Event { event } => commands::event::execute(event).await,
         Commands::Positions { user } => commands::positions::execute(user).await,
-        Commands::OrderBook { token_id } => commands::orderbook::execute(token_id).await,
-        Commands::Trade { token_id } => commands::trade::execute(token_id).await,
-        Commands::Midpoint { token_id } => commands::midpoint::execute(token_id).await,
-        Commands::Order { token_id, side, amount, price } => {
+        Commands::OrderBook { token, watch, depth, sizes } => {
+            let token_id = commands::market::resolve_token(token).await?;
+            if watch {
+                commands::orderbook::watch(token_id, depth).await
+            } else {
+                commands::orderbook::execute(token_id, depth, sizes).await
+            }
+        }
+        Commands::Trade { token, follow, min_size, user, side, from, to, limit } => {
+            let selected = commands::market::resolve_outcomes(token).await?;
+            if follow {
+                commands::trade::follow(selected, min_size).await
+            } else {
+                commands::trade::execute(selected, user, side, from, to, limit).await
+            }
+        }
+        Commands::Midpoint { token } => {
+            let token_id = commands::market::resolve_token(token).await?;
+            commands::midpoint::execute(token_id).await
+        }
+        Commands::History { token, interval, from, to, ohlc, format, chart } => {
+            let token_id = commands::market::resolve_token(token).await?;
+            commands::history::execute(token_id, interval, from, to, ohlc, format, chart).await
+        }
+        Commands::Order { token, side, amount, price } => {
+            let token_id = commands::market::resolve_token_exact(token).await?;
             commands::order::execute(token_id, side, amount, price).await
         }
-        Commands::Status => commands::status::execute().await,
-        Commands::Approve { dry_run } => commands::approve::execute(dry_run).await,
+        Commands::Fills { user, onchain, from_block, to_block, chunk_size } => {
+            commands::fills::execute(user, onchain, from_block, to_block, chunk_size, &cli.rpc_url).await
+        }
+        Commands::Approvals { command } => commands::approvals::execute(command, &cli.rpc_url).await,
+        Commands::Status => commands::status::execute(&cli.rpc_url).await,
+        Commands::Balance { token, user } => commands::balance::execute(token, user, &cli.rpc_url).await,
+        Commands::Approve { dry_run } => commands::approve::execute(dry_run, &cli.rpc_url).await,
+        Commands::Redeem { market, dry_run } => commands::redeem::execute(market, dry_run, &cli.rpc_url).await,
+        Commands::Split { market, amount, dry_run } => {
+            commands::split::execute(market, amount, dry_run, &cli.rpc_url).await
+        }
+        Commands::Merge { market, amount, max, dry_run } => {
+            commands::merge::execute(market, amount, max, dry_run, &cli.rpc_url).await
+        }
+        Commands::Convert { market, amount, max, dry_run } => {
+            commands::convert::execute(market, amount, max, dry_run, &cli.rpc_url).await
+        }
+        Commands::Reconcile { user } => commands::reconcile::execute(user, &cli.rpc_url).await,
+        Commands::Transfer { to, amount, token, token_id, from, yes } => {
+            commands::transfer::execute(to, amount, token, token_id, from, yes, &cli.rpc_url).await
+        }
+        Commands::Screen { filters, sort, max_markets, limit } => {
+            commands::screen::execute(filters, sort, max_markets, limit).await
+        }
+        Commands::Scan { command } => commands::scan::execute(command).await,
+        Commands::Ctf { command } => commands::ctf::execute(command).await,
         Commands::Upgrade => commands::upgrade::execute().await,
     }
 }
@@ -0,0 +1,437 @@
+use alloy::primitives::U64;
+use alloy::providers::Provider;
+use alloy::rpc::client::{ClientBuilder, RpcClient};
+use alloy::rpc::json_rpc::{RequestPacket, ResponsePacket};
+use alloy::rpc::types::{Filter, Log};
+use alloy::transports::http::{Http, reqwest::{Client, Url}};
+use alloy::transports::{RpcError, TransportError, TransportErrorKind, TransportFut};
+use anyhow::{Context, Result};
+use futures::stream::{FuturesUnordered, StreamExt};
+use polymarket_client_sdk::POLYGON;
+use std::env;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::{Context as TaskContext, Poll};
+use std::time::Duration;
+use tower::Service;
+
+use crate::constants::RPC_URLS;
+
+/// Environment variable holding a comma-separated list of RPC URLs.
+pub const RPC_URL_VAR: &str = "POLYGON_RPC_URL";
+
+/// Retries per endpoint on transient errors before failing over to the next one.
+const MAX_RETRIES: u32 = 3;
+const INITIAL_BACKOFF: Duration = Duration::from_millis(250);
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+/// Methods that must reach a node at most once: a retried send of a transaction that
+/// was already accepted fails with "already known" or "nonce too low".
+const NON_IDEMPOTENT_METHODS: &[&str] = &["eth_sendRawTransaction", "eth_sendTransaction"];
+/// Smallest block range to fall back to when an RPC rejects a log query.
+const MIN_LOG_CHUNK_SIZE: u64 = 100;
+/// Accepted chunks in a row after which a shrunk chunk size doubles again.
+const GROW_LOG_CHUNK_AFTER: u32 = 8;
+
+/// Connect to Polygon through every configured endpoint, with retry and failover.
+///
+/// Endpoints come from `--rpc-url` if given, otherwise `POLYGON_RPC_URL`, otherwise
+/// the built-in `RPC_URLS`. Endpoints are health checked with `eth_chainId`
+/// concurrently; the first one to pass becomes active, without waiting for the others.
+/// The rest follow in the configured order, with any that already failed last.
+pub async fn connect(rpc_urls: &[String]) -> Result<RpcClient> {
+    let urls = resolve_rpc_urls(rpc_urls, env::var(RPC_URL_VAR).ok());
+    let urls = urls
+        .iter()
+        .map(|u| Url::parse(u).with_context(|| format!("Invalid RPC URL: {}", u)))
+        .collect::<Result<Vec<_>>>()?;
+    anyhow::ensure!(!urls.is_empty(), "No RPC URLs configured");
+
+    let mut checks: FuturesUnordered<_> = urls
+        .iter()
+        .enumerate()
+        .map(|(index, url)| async move { (index, health_check(url.clone()).await) })
+        .collect();
+    let mut first_healthy = None;
+    let mut failed = Vec::new();
+    while let Some((index, result)) = checks.next().await {
+        match result {
+            Ok(()) => {
+                log::debug!("rpc = {}, healthy", urls[index]);
+                first_healthy = Some(index);
+                break;
+            }
+            Err(e) => {
+                log::warn!("rpc = {}, error = {:#}, health check failed", urls[index], e);
+                failed.push(index);
+            }
+        }
+    }
+    // Abandon the checks still in flight
+    drop(checks);
+    if first_healthy.is_none() {
+        log::warn!("no RPC endpoint passed the health check, trying all of them anyway");
+    }
+
+    let endpoints = failover_order(urls.len(), first_healthy, &failed).into_iter().map(|i| urls[i].clone()).collect();
+    Ok(ClientBuilder::default().transport(FailoverTransport::new(endpoints), false))
+}
+
+/// Endpoint order for failover: the first healthy endpoint, then the unchecked ones in
+/// configured order, then those that failed their health check.
+fn failover_order(count: usize, first_healthy: Option<usize>, failed: &[usize]) -> Vec<usize> {
+    let rest = (0..count).filter(|i| Some(*i) != first_healthy && !failed.contains(i));
+    first_healthy.into_iter().chain(rest).chain(failed.iter().copied()).collect()
+}
+
+/// Pick the RPC URL list: explicit flags, then the env var (comma-separated), then the defaults.
+pub fn resolve_rpc_urls(flags: &[String], env_value: Option<String>) -> Vec<String> {
+    if !flags.is_empty() {
+        return flags.to_vec();
+    }
+    if let Some(value) = env_value {
+        let urls: Vec<String> = value
+            .split(',')
+            .map(str::trim)
+            .filter(|u| !u.is_empty())
+            .map(String::from)
+            .collect();
+        if !urls.is_empty() {
+            return urls;
+        }
+    }
+    RPC_URLS.iter().map(|u| u.to_string()).collect()
+}
+
+/// Check that an endpoint answers `eth_chainId` with Polygon's chain ID.
+async fn health_check(url: Url) -> Result<()> {
+    let client = ClientBuilder::default().http(url);
+    let chain_id: U64 = tokio::time::timeout(HEALTH_CHECK_TIMEOUT, client.request_noparams("eth_chainId"))
+        .await
+        .context("timed out")??;
+    anyhow::ensure!(chain_id.to::<u64>() == POLYGON, "wrong chain ID {}", chain_id);
+    Ok(())
+}
+
+/// Exponential backoff before retry number `attempt` (starting at 0).
+pub fn backoff_delay(attempt: u32) -> Duration {
+    INITIAL_BACKOFF * 2u32.saturating_pow(attempt)
+}
+
+/// Whether a request may be sent more than once, to the same or another endpoint.
+pub fn is_idempotent(methods: &[&str]) -> bool {
+    !methods.iter().any(|m| NON_IDEMPOTENT_METHODS.contains(m))
+}
+
+/// Whether an error is worth retrying: rate limits, server errors, dropped connections.
+pub fn is_transient(err: &TransportError) -> bool {
+    match err {
+        RpcError::Transport(TransportErrorKind::HttpError(e)) => e.status == 429 || e.status >= 500,
+        // Connection resets, DNS failures and timeouts surface as custom reqwest errors.
+        RpcError::Transport(TransportErrorKind::Custom(_)) => true,
+        RpcError::Transport(kind) => kind.is_retry_err(),
+        RpcError::ErrorResp(payload) => payload.is_retry_err(),
+        RpcError::NullResp => true,
+        _ => false,
+    }
+}
+
+/// Whether an error is down to the endpoint rather than the request, so that another
+/// endpoint may succeed: any HTTP or connection error, or a response that isn't JSON-RPC.
+/// Error responses from the node itself are deterministic and returned as they are.
+pub fn is_endpoint_failure(err: &TransportError) -> bool {
+    matches!(err, RpcError::Transport(_) | RpcError::DeserError { .. } | RpcError::NullResp)
+}
+
+/// Fetch logs matching `filter` between two blocks, `chunk_size` blocks per query.
+///
+/// Public RPCs cap the block range or result size of `eth_getLogs`; when a chunk is
+/// rejected for that it is halved and retried, down to a minimum size, and grows back
+/// towards `chunk_size` after a run of accepted chunks. Other errors end the scan.
+/// Scans of more than one chunk report their progress on stderr.
+pub async fn get_logs_chunked<P: Provider>(
+    provider: &P,
+    filter: &Filter,
+    from_block: u64,
+    to_block: u64,
+    chunk_size: u64,
+) -> Result<Vec<Log>> {
+    anyhow::ensure!(chunk_size > 0, "Chunk size must be positive");
+    let total = to_block.saturating_sub(from_block).saturating_add(1);
+    let show_progress = from_block <= to_block && total > chunk_size;
+    let mut logs = Vec::new();
+    let mut start = from_block;
+    let mut size = chunk_size;
+    let mut accepted = 0;
+    while start <= to_block {
+        let end = chunk_end(start, to_block, size);
+        let range = filter.clone().from_block(start).to_block(end);
+        match provider.get_logs(&range).await {
+            Ok(chunk) => {
+                log::debug!("blocks = {}..={}, logs = {}", start, end, chunk.len());
+                logs.extend(chunk);
+                if show_progress {
+                    eprint!("\rscanned = {}/{} blocks, logs = {}", end - from_block + 1, total, logs.len());
+                }
+                accepted += 1;
+                if size < chunk_size && accepted >= GROW_LOG_CHUNK_AFTER {
+                    size = size.saturating_mul(2).min(chunk_size);
+                    accepted = 0;
+                    log::debug!("chunk_size = {}, growing back", size);
+                }
+                match end.checked_add(1) {
+                    Some(next) => start = next,
+                    None => break,
+                }
+            }
+            Err(e) if is_range_rejection(&e) && size > MIN_LOG_CHUNK_SIZE => {
+                size = (size / 2).max(MIN_LOG_CHUNK_SIZE);
+                accepted = 0;
+                log::warn!("blocks = {}..={}, error = {}, retrying with chunk_size = {}", start, end, e, size);
+            }
+            Err(e) => {
+                if show_progress {
+                    eprintln!();
+                }
+                return Err(e).with_context(|| format!("Failed to fetch logs for blocks {}..={}", start, end));
+            }
+        }
+    }
+    if show_progress {
+        eprintln!();
+    }
+    Ok(logs)
+}
+
+/// Whether a log query was refused for its block range or result size, which a smaller
+/// range can fix: a JSON-RPC error from the node, or HTTP 400 or 413 from its gateway.
+/// Outages and HTTP rate limits, already retried by the transport, are not. Some nodes
+/// answer an oversized query with the "limit exceeded" code they also use for rate
+/// limits, so every JSON-RPC error counts; the chunk size grows back afterwards anyway.
+pub fn is_range_rejection(err: &TransportError) -> bool {
+    match err {
+        RpcError::ErrorResp(_) => true,
+        RpcError::Transport(TransportErrorKind::HttpError(e)) => e.status == 400 || e.status == 413,
+        _ => false,
+    }
+}
+
+/// Last block of the chunk starting at `start`, capped at `to_block`.
+pub fn chunk_end(start: u64, to_block: u64, size: u64) -> u64 {
+    start.saturating_add(size - 1).min(to_block)
+}
+
+/// JSON-RPC transport that spreads requests over several HTTP endpoints.
+///
+/// Requests go to the active endpoint, retrying transient errors with exponential
+/// backoff. When an endpoint keeps failing, or fails in a way retrying can't fix (such
+/// as an HTTP 403 from a key-gated URL), the next one becomes active. Transaction
+/// sends are never retried: they go to the active endpoint exactly once.
+#[derive(Clone)]
+pub struct FailoverTransport {
+    endpoints: Arc<Vec<(Url, Http<Client>)>>,
+    active: Arc<AtomicUsize>,
+}
+
+impl FailoverTransport {
+    pub fn new(urls: Vec<Url>) -> Self {
+        let endpoints = urls.into_iter().map(|url| (url.clone(), Http::new(url))).collect();
+        Self {
+            endpoints: Arc::new(endpoints),
+            active: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    async fn send(self, request: RequestPacket) -> Result<ResponsePacket, TransportError> {
+        let count = self.endpoints.len();
+        let start = self.active.load(Ordering::Relaxed);
+        let method_names = request.method_names().collect::<Vec<_>>();
+        let methods = method_names.join(",");
+        let mut last_err = TransportErrorKind::custom_str("no RPC endpoints configured");
+        let (endpoints, retries) = if is_idempotent(&method_names) { (count, MAX_RETRIES) } else { (1, 0) };
+
+        for offset in 0..endpoints {
+            let index = (start + offset) % count;
+            let (url, http) = &self.endpoints[index];
+            let mut http = http.clone();
+
+            for attempt in 0..=retries {
+                let err = match http.call(request.clone()).await {
+                    Ok(response) => match response.as_error() {
+                        Some(payload) if payload.is_retry_err() => TransportError::ErrorResp(payload.clone()),
+                        _ => {
+                            log::info!("rpc = {}, method = {}, served", host(url), methods);
+                            if index != start {
+                                self.active.store(index, Ordering::Relaxed);
+                            }
+                            return Ok(response);
+                        }
+                    },
+                    Err(e) => e,
+                };
+
+                if !is_transient(&err) {
+                    if !is_endpoint_failure(&err) {
+                        return Err(err);
+                    }
+                    // Another endpoint may well serve it: a gated or dead URL, a broken gateway
+                    log::warn!("rpc = {}, method = {}, error = {}, endpoint failure", host(url), methods, err);
+                    last_err = err;
+                    break;
+                }
+
+                log::warn!("rpc = {}, method = {}, attempt = {}, error = {}, transient failure", host(url), methods, attempt + 1, err);
+                last_err = err;
+                if attempt < retries {
+                    tokio::time::sleep(backoff_delay(attempt)).await;
+                }
+            }
+
+            if offset + 1 < endpoints {
+                log::warn!("rpc = {}, failing over to next endpoint", host(url));
+            }
+        }
+
+        Err(last_err)
+    }
+}
+
+impl Service<RequestPacket> for FailoverTransport {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut TaskContext<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        Box::pin(self.clone().send(request))
+    }
+}
+
+fn host(url: &Url) -> &str {
+    url.host_str().unwrap_or(url.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::transports::HttpError;
+
+    #[test]
+    fn test_resolve_rpc_urls_flags_win() {
+        let flags = vec!["https://a.example".to_string()];
+        let urls = resolve_rpc_urls(&flags, Some("https://b.example".to_string()));
+        assert_eq!(urls, flags);
+    }
+
+    #[test]
+    fn test_resolve_rpc_urls_env_comma_separated() {
+        let urls = resolve_rpc_urls(&[], Some(" https://a.example, https://b.example ,".to_string()));
+        assert_eq!(urls, vec!["https://a.example", "https://b.example"]);
+    }
+
+    #[test]
+    fn test_resolve_rpc_urls_defaults() {
+        let urls = resolve_rpc_urls(&[], None);
+        assert_eq!(urls.len(), RPC_URLS.len());
+        assert_eq!(urls[0], RPC_URLS[0]);
+
+        let urls = resolve_rpc_urls(&[], Some(" ".to_string()));
+        assert_eq!(urls.len(), RPC_URLS.len());
+    }
+
+    #[test]
+    fn test_backoff_delay_doubles() {
+        assert_eq!(backoff_delay(0), Duration::from_millis(250));
+        assert_eq!(backoff_delay(1), Duration::from_millis(500));
+        assert_eq!(backoff_delay(2), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_failover_order() {
+        assert_eq!(failover_order(4, Some(2), &[1]), vec![2, 0, 3, 1]);
+        assert_eq!(failover_order(3, None, &[0, 2, 1]), vec![0, 2, 1]);
+        assert_eq!(failover_order(2, Some(0), &[]), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_is_idempotent() {
+        assert!(is_idempotent(&["eth_call", "eth_getLogs"]));
+        assert!(!is_idempotent(&["eth_sendRawTransaction"]));
+        assert!(!is_idempotent(&["eth_chainId", "eth_sendTransaction"]));
+    }
+
+    #[test]
+    fn test_chunk_end() {
+        assert_eq!(chunk_end(100, 10_000, 2_000), 2_099);
+        assert_eq!(chunk_end(9_500, 10_000, 2_000), 10_000);
+        assert_eq!(chunk_end(5, 5, 1), 5);
+        assert_eq!(chunk_end(u64::MAX - 1, u64::MAX, 10), u64::MAX);
+    }
+
+    #[test]
+    fn test_is_transient_http_status() {
+        let http = |status| RpcError::Transport(TransportErrorKind::HttpError(HttpError { status, body: String::new() }));
+        assert!(is_transient(&http(429)));
+        assert!(is_transient(&http(502)));
+        assert!(!is_transient(&http(401)));
+    }
+
+    #[test]
+    fn test_is_transient_null_and_local() {
+        assert!(is_transient(&RpcError::NullResp));
+        assert!(!is_transient(&RpcError::UnsupportedFeature("x")));
+    }
+
+    #[test]
+    fn test_is_range_rejection() {
+        let http = |status| RpcError::Transport(TransportErrorKind::HttpError(HttpError { status, body: String::new() }));
+        let payload = |code| RpcError::ErrorResp(alloy::rpc::json_rpc::ErrorPayload { code, message: "query returned more than 10000 results".into(), data: None });
+        assert!(is_range_rejection(&payload(-32005_i64)));
+        assert!(is_range_rejection(&payload(-32602_i64)));
+        assert!(is_range_rejection(&http(413)));
+        assert!(!is_range_rejection(&http(429)));
+        assert!(!is_range_rejection(&http(503)));
+        assert!(!is_range_rejection(&RpcError::NullResp));
+    }
+
+    #[test]
+    fn test_is_endpoint_failure() {
+        let http = RpcError::Transport(TransportErrorKind::HttpError(HttpError { status: 403, body: String::new() }));
+        assert!(is_endpoint_failure(&http));
+        assert!(!is_endpoint_failure(&RpcError::UnsupportedFeature("x")));
+    }
+
+    /// Answer every HTTP request on a local port with the given status line and body.
+    async fn serve_http(status: &'static str, body: &'static str) -> Url {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = vec![0; 8192];
+                let _ = socket.read(&mut buf).await;
+                let response = format!(
+                    "HTTP/1.1 {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_failover_on_forbidden_endpoint() {
+        let forbidden = serve_http("403 Forbidden", "forbidden").await;
+        let healthy = serve_http("200 OK", r#"{"jsonrpc":"2.0","id":0,"result":"0x89"}"#).await;
+        let transport = FailoverTransport::new(vec![forbidden, healthy]);
+        let client = ClientBuilder::default().transport(transport.clone(), true);
+        let chain_id: U64 = client.request_noparams("eth_chainId").await.unwrap();
+        assert_eq!(chain_id.to::<u64>(), POLYGON);
+        assert_eq!(transport.active.load(Ordering::Relaxed), 1);
+    }
+}
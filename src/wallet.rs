@@ -0,0 +1,191 @@
+use alloy::primitives::{B256, Bytes, U256};
+use alloy::providers::Provider;
+use alloy::rpc::types::TransactionRequest;
+use alloy::sol_types::SolCall;
+use anyhow::{Context, Result};
+use std::env;
+use polymarket_client_sdk::{
+    POLYGON,
+    clob::types::SignatureType,
+    derive_proxy_wallet, derive_safe_wallet, wallet_contract_config,
+    types::Address,
+};
+
+use crate::contracts::{IGnosisSafe, IProxyWalletFactory};
+
+/// Proxy wallet call type for a plain CALL (0 is invalid, 2 is DELEGATECALL).
+const PROXY_CALL_TYPE: u8 = 1;
+
+/// Environment variable selecting the wallet on-chain commands act for: "eoa", "proxy" (default) or "safe".
+pub const SIGNATURE_TYPE_VAR: &str = "POLYMARKET_SIGNATURE_TYPE";
+
+/// Read the configured signature type, defaulting to the Polymarket proxy wallet.
+pub fn signature_type_from_env() -> Result<SignatureType> {
+    match env::var(SIGNATURE_TYPE_VAR) {
+        Ok(value) => parse_signature_type(&value),
+        Err(_) => Ok(SignatureType::Proxy),
+    }
+}
+
+/// Parse a signature type string ("eoa", "proxy" or "safe") into the SignatureType enum.
+pub fn parse_signature_type(value: &str) -> Result<SignatureType> {
+    match value.to_lowercase().as_str() {
+        "eoa" | "0" => Ok(SignatureType::Eoa),
+        "proxy" | "1" => Ok(SignatureType::Proxy),
+        "safe" | "gnosis-safe" | "2" => Ok(SignatureType::GnosisSafe),
+        _ => anyhow::bail!("Invalid signature type: must be 'eoa', 'proxy' or 'safe'"),
+    }
+}
+
+/// The wallet that holds funds and positions for a signature type.
+pub fn funder_address(owner: Address, signature_type: SignatureType) -> Result<Address> {
+    match signature_type {
+        SignatureType::Eoa => Ok(owner),
+        SignatureType::GnosisSafe => derive_safe_wallet(owner, POLYGON).context("Failed to derive safe wallet"),
+        _ => derive_proxy_wallet(owner, POLYGON).context("Failed to derive proxy wallet"),
+    }
+}
+
+/// Wrap a contract call so that it executes from the funding wallet.
+/// Returns the `(to, data)` of the transaction the EOA has to send.
+///
+/// Proxy wallets are driven through the proxy factory's `proxy()`, which forwards
+/// to the caller's proxy. Safes are driven with `execTransaction`, approved by the
+/// owner being the sender.
+pub fn route_call(owner: Address, signature_type: SignatureType, to: Address, data: Bytes) -> Result<(Address, Bytes)> {
+    match signature_type {
+        SignatureType::Eoa => Ok((to, data)),
+        SignatureType::GnosisSafe => {
+            let safe = derive_safe_wallet(owner, POLYGON).context("Failed to derive safe wallet")?;
+            let call = IGnosisSafe::execTransactionCall {
+                to,
+                value: U256::ZERO,
+                data,
+                operation: 0,
+                safeTxGas: U256::ZERO,
+                baseGas: U256::ZERO,
+                gasPrice: U256::ZERO,
+                gasToken: Address::ZERO,
+                refundReceiver: Address::ZERO,
+                signatures: prevalidated_signature(owner),
+            };
+            Ok((safe, call.abi_encode().into()))
+        }
+        _ => {
+            let factory = wallet_contract_config(POLYGON)
+                .and_then(|c| c.proxy_factory)
+                .context("No proxy wallet factory configured")?;
+            let call = IProxyWalletFactory::proxyCall {
+                calls: vec![IProxyWalletFactory::ProxyCall {
+                    typeCode: PROXY_CALL_TYPE,
+                    to,
+                    value: U256::ZERO,
+                    data,
+                }],
+            };
+            Ok((factory, call.abi_encode().into()))
+        }
+    }
+}
+
+/// Safe signature approving a transaction because its owner is the one sending it:
+/// `r` is the owner address, `s` is zero and `v` is 1.
+pub fn prevalidated_signature(owner: Address) -> Bytes {
+    let mut signature = Vec::with_capacity(65);
+    signature.extend_from_slice(owner.into_word().as_slice());
+    signature.extend_from_slice(B256::ZERO.as_slice());
+    signature.push(1);
+    signature.into()
+}
+
+/// Send a contract call from the funding wallet and wait for it to be mined.
+pub async fn send_call<P: Provider>(
+    provider: &P,
+    owner: Address,
+    signature_type: SignatureType,
+    to: Address,
+    data: Bytes,
+) -> Result<B256> {
+    let (to, data) = route_call(owner, signature_type, to, data)?;
+    let tx = TransactionRequest::default().from(owner).to(to).input(data.into());
+    let receipt = provider
+        .send_transaction(tx)
+        .await
+        .context("Failed to send transaction")?
+        .get_receipt()
+        .await
+        .context("Failed to get transaction receipt")?;
+    anyhow::ensure!(receipt.status(), "transaction {} reverted", receipt.transaction_hash);
+    Ok(receipt.transaction_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polymarket_client_sdk::types::address;
+
+    const EOA: Address = address!("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+
+    #[test]
+    fn test_parse_signature_type() {
+        assert!(matches!(parse_signature_type("eoa"), Ok(SignatureType::Eoa)));
+        assert!(matches!(parse_signature_type("Proxy"), Ok(SignatureType::Proxy)));
+        assert!(matches!(parse_signature_type("SAFE"), Ok(SignatureType::GnosisSafe)));
+        assert!(matches!(parse_signature_type("2"), Ok(SignatureType::GnosisSafe)));
+        assert!(parse_signature_type("multisig").is_err());
+    }
+
+    #[test]
+    fn test_funder_address() {
+        assert_eq!(funder_address(EOA, SignatureType::Eoa).unwrap(), EOA);
+        assert_eq!(
+            funder_address(EOA, SignatureType::Proxy).unwrap(),
+            address!("0x365f0cA36ae1F641E02Fe3b7743673DA42A13a70")
+        );
+        assert_eq!(
+            funder_address(EOA, SignatureType::GnosisSafe).unwrap(),
+            address!("0xd93b25Cb943D14d0d34FBAf01fc93a0F8b5f6e47")
+        );
+    }
+
+    #[test]
+    fn test_route_call_eoa_is_direct() {
+        let target = Address::repeat_byte(0x11);
+        let data = Bytes::from(vec![1, 2, 3]);
+        let (to, routed) = route_call(EOA, SignatureType::Eoa, target, data.clone()).unwrap();
+        assert_eq!(to, target);
+        assert_eq!(routed, data);
+    }
+
+    #[test]
+    fn test_route_call_proxy_goes_through_factory() {
+        let target = Address::repeat_byte(0x11);
+        let (to, routed) = route_call(EOA, SignatureType::Proxy, target, Bytes::from(vec![1, 2, 3])).unwrap();
+        assert_eq!(Some(to), wallet_contract_config(POLYGON).unwrap().proxy_factory);
+
+        let decoded = IProxyWalletFactory::proxyCall::abi_decode(&routed).unwrap();
+        assert_eq!(decoded.calls.len(), 1);
+        assert_eq!(decoded.calls[0].typeCode, PROXY_CALL_TYPE);
+        assert_eq!(decoded.calls[0].to, target);
+    }
+
+    #[test]
+    fn test_route_call_safe_uses_exec_transaction() {
+        let target = Address::repeat_byte(0x11);
+        let (to, routed) = route_call(EOA, SignatureType::GnosisSafe, target, Bytes::new()).unwrap();
+        assert_eq!(to, funder_address(EOA, SignatureType::GnosisSafe).unwrap());
+
+        let decoded = IGnosisSafe::execTransactionCall::abi_decode(&routed).unwrap();
+        assert_eq!(decoded.to, target);
+        assert_eq!(decoded.signatures, prevalidated_signature(EOA));
+    }
+
+    #[test]
+    fn test_prevalidated_signature_layout() {
+        let signature = prevalidated_signature(EOA);
+        assert_eq!(signature.len(), 65);
+        assert_eq!(&signature[12..32], EOA.as_slice());
+        assert!(signature[32..64].iter().all(|b| *b == 0));
+        assert_eq!(signature[64], 1);
+    }
+}
@@ -0,0 +1,401 @@
+//! Client for the CLOB WebSocket market channel, with heartbeats and automatic reconnects.
+
+use anyhow::{Context, Result};
+use futures::{SinkExt, StreamExt};
+use polymarket_client_sdk::{
+    clob::types::{Side, response::OrderSummary},
+    types::Decimal,
+};
+use serde::{Deserialize, Deserializer};
+use std::collections::VecDeque;
+use std::env;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::time::Instant;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, tungstenite::Message};
+
+use crate::rpc::backoff_delay;
+
+/// Public market channel of the CLOB.
+pub const MARKET_CHANNEL_URL: &str = "wss://ws-subscriptions-clob.polymarket.com/ws/market";
+/// Environment variable overriding the market channel URL, e.g. to point at a local server.
+pub const MARKET_CHANNEL_URL_VAR: &str = "POLYMARKET_WS_URL";
+
+/// The server drops connections that stay silent for longer than this.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+/// Reconnect when nothing, not even a PONG, arrived for this long.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// Resyncs closer together than this back off like failed connections.
+const RESYNC_WINDOW: Duration = Duration::from_secs(60);
+
+/// The market channel URL: `POLYMARKET_WS_URL` if set, otherwise the public endpoint.
+pub fn market_channel_url() -> String {
+    env::var(MARKET_CHANNEL_URL_VAR).unwrap_or_else(|_| MARKET_CHANNEL_URL.to_string())
+}
+
+/// A message of the market channel. Event types this CLI doesn't use are `Other`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "event_type", rename_all = "snake_case")]
+pub enum MarketMessage {
+    /// Full snapshot of a token's book, sent on subscribe and after trades.
+    Book(BookMessage),
+    /// Changed price levels, for one or more tokens.
+    PriceChange(PriceChangeMessage),
+    /// A trade was matched.
+    LastTradePrice(LastTradeMessage),
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BookMessage {
+    pub asset_id: String,
+    /// Unix time in milliseconds.
+    #[serde(deserialize_with = "millis")]
+    pub timestamp: i64,
+    #[serde(default)]
+    pub bids: Vec<OrderSummary>,
+    #[serde(default)]
+    pub asks: Vec<OrderSummary>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PriceChangeMessage {
+    #[serde(deserialize_with = "millis")]
+    pub timestamp: i64,
+    #[serde(default)]
+    pub price_changes: Vec<PriceChange>,
+}
+
+/// New total size at one price level; a size of zero removes the level. `Buy` changes
+/// the bids, `Sell` the asks.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PriceChange {
+    pub asset_id: String,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub side: Side,
+    /// Best bid and ask of the token's book once the change is applied.
+    #[serde(default)]
+    pub best_bid: Option<Decimal>,
+    #[serde(default)]
+    pub best_ask: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LastTradeMessage {
+    pub asset_id: String,
+    pub price: Decimal,
+    /// Side of the taker.
+    #[serde(default)]
+    pub side: Option<Side>,
+    #[serde(default)]
+    pub size: Option<Decimal>,
+    #[serde(deserialize_with = "millis")]
+    pub timestamp: i64,
+}
+
+/// Timestamps arrive as strings of milliseconds, occasionally as numbers.
+fn millis<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<i64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Number(i64),
+        Text(String),
+    }
+    match Raw::deserialize(deserializer)? {
+        Raw::Number(n) => Ok(n),
+        Raw::Text(s) => s.parse().map_err(serde::de::Error::custom),
+    }
+}
+
+/// Parse one text frame. The server sends single messages as objects and the initial
+/// snapshots as an array; messages that don't parse are skipped.
+pub fn parse_frame(text: &str) -> Vec<MarketMessage> {
+    let value: serde_json::Value = match serde_json::from_str(text) {
+        Ok(value) => value,
+        Err(e) => {
+            log::debug!("frame = {}, error = {}, skipping unparsable frame", text, e);
+            return Vec::new();
+        }
+    };
+    let values = match value {
+        serde_json::Value::Array(values) => values,
+        value => vec![value],
+    };
+    values
+        .into_iter()
+        .filter_map(|v| match serde_json::from_value(v) {
+            Ok(message) => Some(message),
+            Err(e) => {
+                log::debug!("error = {}, skipping unknown message", e);
+                None
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub enum StreamEvent {
+    /// A new connection was subscribed. Anything built from the previous connection's
+    /// messages is stale; fresh book snapshots follow.
+    Connected,
+    Message(MarketMessage),
+}
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Subscription to the market channel for a set of tokens that survives disconnects:
+/// dropped or silent connections are reopened with exponential backoff and resubscribed.
+/// The backoff only resets once a connection delivers a book snapshot, so a server that
+/// accepts and then drops the socket is redialled less and less often.
+pub struct MarketStream {
+    url: String,
+    asset_ids: Vec<String>,
+    socket: Option<Socket>,
+    pending: VecDeque<MarketMessage>,
+    failures: u32,
+    /// Consecutive resyncs less than `RESYNC_WINDOW` apart.
+    resyncs: u32,
+    last_resync: Option<Instant>,
+    last_ping: Instant,
+    last_received: Instant,
+}
+
+impl MarketStream {
+    pub fn new(url: impl Into<String>, asset_ids: Vec<String>) -> Self {
+        MarketStream {
+            url: url.into(),
+            asset_ids,
+            socket: None,
+            pending: VecDeque::new(),
+            failures: 0,
+            resyncs: 0,
+            last_resync: None,
+            last_ping: Instant::now(),
+            last_received: Instant::now(),
+        }
+    }
+
+    /// Wait for the next event, connecting first if needed. Connection errors are logged
+    /// and retried, never returned.
+    pub async fn next(&mut self) -> StreamEvent {
+        loop {
+            if let Some(message) = self.pending.pop_front() {
+                return StreamEvent::Message(message);
+            }
+            let Some(socket) = self.socket.as_mut() else {
+                if let Some(delay) = reconnect_delay(self.failures + self.resyncs) {
+                    tokio::time::sleep(delay).await;
+                }
+                match connect(&self.url, &self.asset_ids).await {
+                    Ok(socket) => {
+                        log::debug!("url = {}, tokens = {}, subscribed", self.url, self.asset_ids.len());
+                        self.socket = Some(socket);
+                        self.last_ping = Instant::now();
+                        self.last_received = Instant::now();
+                        return StreamEvent::Connected;
+                    }
+                    Err(e) => {
+                        self.failures += 1;
+                        log::warn!("url = {}, attempt = {}, error = {:#}, connect failed", self.url, self.failures, e);
+                        continue;
+                    }
+                }
+            };
+
+            if self.last_received.elapsed() >= READ_TIMEOUT {
+                log::warn!("url = {}, no data for {:?}, reconnecting", self.url, READ_TIMEOUT);
+                self.drop_connection(true);
+                continue;
+            }
+            if self.last_ping.elapsed() >= HEARTBEAT_INTERVAL {
+                self.last_ping = Instant::now();
+                if let Err(e) = socket.send(Message::Text("PING".into())).await {
+                    log::warn!("url = {}, error = {}, heartbeat failed", self.url, e);
+                    self.drop_connection(true);
+                    continue;
+                }
+            }
+
+            let wait = HEARTBEAT_INTERVAL.saturating_sub(self.last_ping.elapsed());
+            match tokio::time::timeout(wait, socket.next()).await {
+                // Time for the next heartbeat
+                Err(_) => continue,
+                Ok(Some(Ok(Message::Text(text)))) => {
+                    self.last_received = Instant::now();
+                    if text.as_str() != "PONG" {
+                        let messages = parse_frame(text.as_str());
+                        if messages.iter().any(|m| matches!(m, MarketMessage::Book(_))) {
+                            self.failures = 0;
+                        }
+                        self.pending.extend(messages);
+                    }
+                }
+                Ok(Some(Ok(Message::Close(_)))) | Ok(None) => {
+                    log::warn!("url = {}, connection closed by server", self.url);
+                    self.drop_connection(true);
+                }
+                Ok(Some(Ok(_))) => self.last_received = Instant::now(),
+                Ok(Some(Err(e))) => {
+                    log::warn!("url = {}, error = {}, connection lost", self.url, e);
+                    self.drop_connection(true);
+                }
+            }
+        }
+    }
+
+    /// Reconnect to get fresh snapshots, e.g. when a local book fell out of sync.
+    /// Resyncs in quick succession wait longer and longer before reconnecting.
+    pub fn resync(&mut self) {
+        let recent = self.last_resync.is_some_and(|at| at.elapsed() < RESYNC_WINDOW);
+        self.resyncs = if recent { self.resyncs + 1 } else { 1 };
+        self.last_resync = Some(Instant::now());
+        self.drop_connection(false);
+    }
+
+    fn drop_connection(&mut self, failed: bool) {
+        self.socket = None;
+        self.pending.clear();
+        if failed {
+            self.failures += 1;
+        }
+    }
+}
+
+/// Wait before reconnecting after `attempts` failed connections or resyncs, if any.
+fn reconnect_delay(attempts: u32) -> Option<Duration> {
+    attempts.checked_sub(1).map(|n| backoff_delay(n).min(MAX_BACKOFF))
+}
+
+async fn connect(url: &str, asset_ids: &[String]) -> Result<Socket> {
+    let (mut socket, _) = tokio_tungstenite::connect_async(url).await.context("Failed to connect")?;
+    let subscribe = serde_json::json!({ "type": "market", "assets_ids": asset_ids, "initial_dump": true });
+    socket.send(Message::Text(subscribe.to_string().into())).await.context("Failed to subscribe")?;
+    Ok(socket)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// A market channel stand-in that serves one scripted session per connection and
+    /// returns the subscription requests it received.
+    async fn serve(sessions: Vec<Vec<&'static str>>) -> (String, tokio::task::JoinHandle<Vec<serde_json::Value>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let mut subscriptions = Vec::new();
+            for frames in sessions {
+                let (tcp, _) = listener.accept().await.unwrap();
+                let mut socket = tokio_tungstenite::accept_async(tcp).await.unwrap();
+                let Some(Ok(Message::Text(request))) = socket.next().await else { panic!("no subscription") };
+                subscriptions.push(serde_json::from_str(request.as_str()).unwrap());
+                for frame in frames {
+                    socket.send(Message::Text(frame.into())).await.unwrap();
+                }
+                socket.close(None).await.unwrap();
+            }
+            subscriptions
+        });
+        (url, handle)
+    }
+
+    fn kinds(events: &[StreamEvent]) -> Vec<&'static str> {
+        events
+            .iter()
+            .map(|e| match e {
+                StreamEvent::Connected => "connected",
+                StreamEvent::Message(MarketMessage::Book(_)) => "book",
+                StreamEvent::Message(MarketMessage::PriceChange(_)) => "price_change",
+                StreamEvent::Message(MarketMessage::LastTradePrice(_)) => "last_trade_price",
+                StreamEvent::Message(MarketMessage::Other) => "other",
+            })
+            .collect()
+    }
+
+    const BOOK: &str = r#"[{"event_type":"book","asset_id":"1","market":"0xc","timestamp":"1700000000000",
+        "bids":[{"price":"0.48","size":"10"}],"asks":[{"price":"0.52","size":"20"}],"hash":"abc"}]"#;
+    const CHANGE: &str = r#"{"event_type":"price_change","market":"0xc","timestamp":"1700000000100",
+        "price_changes":[{"asset_id":"1","price":"0.49","size":"5","side":"BUY","best_bid":"0.49","best_ask":"0.52"}]}"#;
+
+    #[test]
+    fn test_parse_frame() {
+        let messages = parse_frame(CHANGE);
+        let [MarketMessage::PriceChange(change)] = messages.as_slice() else { panic!("{:?}", messages) };
+        assert_eq!(change.timestamp, 1_700_000_000_100);
+        assert_eq!(change.price_changes[0].side, Side::Buy);
+        assert_eq!(change.price_changes[0].best_bid, Some(Decimal::new(49, 2)));
+
+        let trade = r#"{"event_type":"last_trade_price","asset_id":"1","market":"0xc","price":"0.5",
+            "side":"SELL","size":"12.5","timestamp":1700000000200}"#;
+        let messages = parse_frame(trade);
+        let [MarketMessage::LastTradePrice(trade)] = messages.as_slice() else { panic!("{:?}", messages) };
+        assert_eq!(trade.side, Some(Side::Sell));
+        assert_eq!(trade.timestamp, 1_700_000_000_200);
+
+        assert!(matches!(parse_frame(r#"{"event_type":"tick_size_change"}"#).as_slice(), [MarketMessage::Other]));
+        assert!(parse_frame("not json").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stream_reconnects_and_resubscribes() {
+        let (url, server) = serve(vec![vec![BOOK, CHANGE, "PONG"], vec![BOOK]]).await;
+        let mut stream = MarketStream::new(url, vec!["1".to_string()]);
+        let mut events = Vec::new();
+        for _ in 0..5 {
+            events.push(stream.next().await);
+        }
+        assert_eq!(kinds(&events), ["connected", "book", "price_change", "connected", "book"]);
+
+        let subscriptions = server.await.unwrap();
+        assert_eq!(subscriptions.len(), 2);
+        assert_eq!(subscriptions[0], serde_json::json!({ "type": "market", "assets_ids": ["1"], "initial_dump": true }));
+    }
+
+    #[tokio::test]
+    async fn test_resync_drops_pending_messages() {
+        let (url, server) = serve(vec![vec![BOOK, CHANGE], vec![BOOK]]).await;
+        let mut stream = MarketStream::new(url, vec!["1".to_string()]);
+        let mut events = vec![stream.next().await, stream.next().await];
+        stream.resync();
+        events.push(stream.next().await);
+        events.push(stream.next().await);
+        assert_eq!(kinds(&events), ["connected", "book", "connected", "book"]);
+        server.await.unwrap();
+    }
+
+    #[test]
+    fn test_reconnect_delay() {
+        assert_eq!(reconnect_delay(0), None);
+        assert_eq!(reconnect_delay(1), Some(Duration::from_millis(250)));
+        assert_eq!(reconnect_delay(3), Some(Duration::from_secs(1)));
+        assert_eq!(reconnect_delay(20), Some(MAX_BACKOFF));
+    }
+
+    #[tokio::test]
+    async fn test_backoff_resets_only_after_a_snapshot() {
+        let (url, server) = serve(vec![vec![], vec![BOOK]]).await;
+        let mut stream = MarketStream::new(url, vec!["1".to_string()]);
+        assert!(matches!(stream.next().await, StreamEvent::Connected));
+        // Closed without data, then accepted again: still backing off
+        assert!(matches!(stream.next().await, StreamEvent::Connected));
+        assert_eq!(stream.failures, 1);
+        assert_eq!(kinds(&[stream.next().await]), ["book"]);
+        assert_eq!(stream.failures, 0);
+        server.await.unwrap();
+    }
+
+    #[test]
+    fn test_resyncs_back_off() {
+        let mut stream = MarketStream::new("ws://127.0.0.1:9", vec!["1".to_string()]);
+        stream.resync();
+        stream.resync();
+        assert_eq!(stream.resyncs, 2);
+        stream.last_resync = Instant::now().checked_sub(RESYNC_WINDOW);
+        stream.resync();
+        assert_eq!(stream.resyncs, 1);
+    }
+}